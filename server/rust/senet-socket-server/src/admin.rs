@@ -0,0 +1,178 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::{
+    fairness::FairnessReport,
+    password::constant_time_eq,
+    shutdown::{self, DrainPhase, DrainReason},
+    stats::GameRecord,
+    store::StoreError,
+    types::{AppState, RoomStatus},
+};
+
+// ========================= 관리자 API =========================
+
+/// 게임 기록 목록의 기본/최대 개수
+const DEFAULT_GAMES_LIMIT: usize = 20;
+const MAX_GAMES_LIMIT: usize = 100;
+/// 점검 드레인 `graceSecs` 최대값 (하루)
+const MAX_DRAIN_GRACE_SECS: u64 = 24 * 60 * 60;
+
+/// `/admin` 아래에 붙는 라우터. 모든 요청에 `Authorization: Bearer <SENET_ADMIN_TOKEN>`이 필요합니다.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/rolls", get(roll_fairness))
+        .route("/games", get(list_games))
+        .route("/games/:game_id", get(get_game))
+        .route(
+            "/drain",
+            get(drain_status).post(start_drain).delete(cancel_drain),
+        )
+}
+
+/// 관리자 토큰 검사. 핸들러 인자로 두면 토큰이 맞을 때만 핸들러가 실행됩니다.
+pub struct Admin;
+
+#[async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, StatusCode> {
+        let Some(expected) = state.admin_token.as_deref() else {
+            return Err(StatusCode::NOT_FOUND);
+        };
+        let given = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or("");
+        if constant_time_eq(given.as_bytes(), expected.as_bytes()) {
+            Ok(Admin)
+        } else {
+            warn!("🔒 관리자 API 인증 실패: {}", parts.uri.path());
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+/// 서버가 만든 모든 게임 굴림에 대한 카이제곱 검정
+async fn roll_fairness(_: Admin, State(state): State<AppState>) -> Json<FairnessReport> {
+    Json(state.rolls.report())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GamesQuery {
+    player_id: Option<String>,
+    limit: Option<usize>,
+}
+
+/// 최근에 끝난 게임 기록 (`?playerId=`로 플레이어별, `?limit=`로 개수 지정)
+async fn list_games(
+    _: Admin,
+    State(state): State<AppState>,
+    Query(q): Query<GamesQuery>,
+) -> Result<Json<Vec<GameRecord>>, StatusCode> {
+    let limit = q.limit.unwrap_or(DEFAULT_GAMES_LIMIT).min(MAX_GAMES_LIMIT);
+    let games = state.games.clone();
+    let result =
+        tokio::task::spawn_blocking(move || games.recent(q.player_id.as_deref(), limit)).await;
+    flatten(result).map(Json)
+}
+
+async fn get_game(
+    _: Admin,
+    State(state): State<AppState>,
+    Path(game_id): Path<String>,
+) -> Result<Json<GameRecord>, StatusCode> {
+    let games = state.games.clone();
+    let result = tokio::task::spawn_blocking(move || games.get(&game_id)).await;
+    flatten(result)?.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// 드레인 단계와 아직 남은 방 수
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DrainStatus {
+    #[serde(flatten)]
+    phase: DrainPhase,
+    rooms: usize,
+    playing_games: usize,
+}
+
+impl DrainStatus {
+    fn of(state: &AppState) -> Json<Self> {
+        Json(DrainStatus {
+            phase: state.drain.phase(),
+            rooms: state.rooms.len(),
+            playing_games: state.lobby.count_status(RoomStatus::Playing),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DrainRequest {
+    grace_secs: Option<u64>,
+}
+
+async fn drain_status(_: Admin, State(state): State<AppState>) -> Json<DrainStatus> {
+    DrainStatus::of(&state)
+}
+
+/// 점검 드레인 시작: 종료 신호와 똑같이 진행하지만 프로세스는 남습니다.
+/// 본문의 `graceSecs`가 없으면 `SENET_SHUTDOWN_GRACE_SECS`를 쓰고, 있으면 `MAX_DRAIN_GRACE_SECS`까지로 맞춥니다.
+async fn start_drain(
+    _: Admin,
+    State(state): State<AppState>,
+    body: Option<Json<DrainRequest>>,
+) -> (StatusCode, Json<DrainStatus>) {
+    let grace = body
+        .and_then(|Json(req)| req.grace_secs)
+        .map(|secs| Duration::from_secs(secs.min(MAX_DRAIN_GRACE_SECS)))
+        .unwrap_or_else(|| state.drain.grace());
+    if !state.drain.begin(DrainReason::Maintenance, grace) {
+        return (StatusCode::CONFLICT, DrainStatus::of(&state));
+    }
+    let drain_state = state.clone();
+    tokio::spawn(async move {
+        shutdown::drain(&drain_state, DrainReason::Maintenance, grace).await;
+    });
+    (StatusCode::ACCEPTED, DrainStatus::of(&state))
+}
+
+/// 점검 드레인 취소 (연결을 이미 닫았으면 다시 받기 시작). 종료 신호로 시작한 드레인은 409.
+async fn cancel_drain(_: Admin, State(state): State<AppState>) -> (StatusCode, Json<DrainStatus>) {
+    let status = if state.drain.cancel() {
+        info!("↩️ 관리자가 드레인을 취소했습니다");
+        StatusCode::OK
+    } else {
+        StatusCode::CONFLICT
+    };
+    (status, DrainStatus::of(&state))
+}
+
+fn flatten<T>(
+    result: Result<Result<T, StoreError>, tokio::task::JoinError>,
+) -> Result<T, StatusCode> {
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => {
+            error!("❌ 게임 기록 조회 실패: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(e) => {
+            error!("❌ 게임 기록 조회 작업 실패: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
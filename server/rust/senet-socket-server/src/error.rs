@@ -0,0 +1,400 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    i18n::{self, Locale},
+    messages::ServerMsg,
+    protocol,
+};
+
+// ========================= 서버 에러 =========================
+
+/// HTTP 상태 코드에 대응하는 에러 분류
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    ClientError,
+    Conflict,
+    NotFound,
+    TooManyRequests,
+    ServerError,
+    Unavailable,
+}
+
+impl ErrorCategory {
+    pub fn status(&self) -> u16 {
+        match self {
+            ErrorCategory::ClientError => 400,
+            ErrorCategory::Conflict => 409,
+            ErrorCategory::NotFound => 404,
+            ErrorCategory::TooManyRequests => 429,
+            ErrorCategory::ServerError => 500,
+            ErrorCategory::Unavailable => 503,
+        }
+    }
+}
+
+/// 클라이언트에게 `ERROR` 메시지로 전달되는 모든 에러.
+/// `code()`가 돌려주는 문자열은 클라이언트와의 계약이므로 변경하지 않습니다.
+/// `Display`는 서버 로그용이며, 클라이언트에게는 `i18n` 카탈로그의 메시지를 보냅니다.
+#[derive(Debug, Clone, Error)]
+pub enum ServerError {
+    /// `INVALID_JSON` - 메시지를 JSON으로 해석할 수 없음
+    #[error("malformed JSON")]
+    InvalidJson { received: String },
+    /// `INVALID_MSGPACK` - 바이너리 프레임을 MessagePack으로 해석할 수 없음
+    #[error("malformed MessagePack frame")]
+    InvalidMsgpack { received_bytes: usize },
+    /// `UNKNOWN_MESSAGE_TYPE` - 지원하지 않는 메시지 타입
+    #[error("unknown message type {received_type}")]
+    UnknownMessageType { received_type: String },
+    /// `INVALID_MESSAGE` - 필수 필드 누락 또는 잘못된 값
+    #[error("invalid field {field} in {msg_type}: {reason}")]
+    InvalidMessage {
+        msg_type: String,
+        field: String,
+        reason: String,
+    },
+    /// `UNSUPPORTED_PROTOCOL_VERSION` - 서버가 받아줄 수 없는 프로토콜 버전 (연결을 끊음)
+    #[error("unsupported protocol version {client_version}")]
+    UnsupportedProtocolVersion { client_version: u32 },
+    /// `HANDSHAKE_TOO_LATE` - 이미 협상했거나 방에 들어간 뒤에 `HELLO`를 보냄
+    #[error("HELLO must be sent once, before joining a room")]
+    HandshakeTooLate,
+    /// `ROOM_NOT_FOUND` - 존재하지 않는 방
+    #[error("room {room_id} not found")]
+    RoomNotFound { room_id: String },
+    /// `INVALID_INVITE_CODE` - 없거나 다시 발급되어 폐기된 초대 코드
+    #[error("invite code {invite_code} is not valid")]
+    InvalidInviteCode { invite_code: String },
+    /// `INVITE_CODE_REQUIRED` - 비공개 방은 초대 코드로만 입장 가능
+    #[error("private room requires an invite code")]
+    InviteCodeRequired,
+    /// `NOT_IN_ROOM` - 방에 참가하지 않은 상태에서 방 메시지를 보냄
+    #[error("not a member of the room")]
+    NotInRoom,
+    /// `INVALID_PASSWORD` - 방 비밀번호 불일치
+    #[error("wrong room password")]
+    InvalidPassword,
+    /// `TOO_MANY_PASSWORD_ATTEMPTS` - 비밀번호를 너무 많이 틀려서 잠시 잠김 (연결별/방별)
+    #[error("too many wrong passwords, retry in {retry_after_secs}s")]
+    TooManyPasswordAttempts { retry_after_secs: u64 },
+    /// `INVALID_SESSION` - 재접속 토큰이 맞지 않거나, 유예 시간이 지나 이미 자리에서 빠짐
+    #[error("session token rejected")]
+    InvalidSession,
+    /// `ROOM_FULL` - 플레이어 자리가 없음
+    #[error("room is full")]
+    RoomFull,
+    /// `SPECTATORS_FULL` - 관전자 자리가 없음
+    #[error("spectator slots are full")]
+    SpectatorsFull,
+    /// `SPECTATING_DISABLED` - 방장이 관전을 막아 둠
+    #[error("spectating is disabled")]
+    SpectatingDisabled,
+    /// `ALREADY_JOINED` - 이미 플레이어로 참가 중
+    #[error("already joined as a player")]
+    AlreadyJoined,
+    /// `ALREADY_SPECTATING` - 이미 관전 중
+    #[error("already spectating")]
+    AlreadySpectating,
+    /// `NO_AVAILABLE_SEATS` - 인원 검사를 통과했는데 빈 좌석이 없음 (서버 상태 불일치)
+    #[error("no free seat despite free player slot")]
+    NoAvailableSeats,
+    /// `BANNED_FROM_ROOM` - 방장이 이 방에서 차단함
+    #[error("banned from this room")]
+    BannedFromRoom,
+    /// `MEMBER_NOT_FOUND` - 방에 없는 플레이어/관전자를 내보내려 함
+    #[error("member {member_id} is not in the room")]
+    MemberNotFound { member_id: String },
+    /// `SEAT_TAKEN` - 다른 플레이어가 앉아 있는 진영
+    #[error("seat {side} is taken")]
+    SeatTaken { side: char },
+    /// `NOT_A_PLAYER` - 관전자에게는 할 수 없는 요청 (예: 방장 넘기기)
+    #[error("member {member_id} is not a player")]
+    NotAPlayer { member_id: String },
+    /// `CANNOT_KICK_SELF` - 방장이 자기 자신을 내보내려 함
+    #[error("the owner cannot kick themselves")]
+    CannotKickSelf,
+    /// `NOT_ROOM_OWNER` - 방장만 할 수 있는 요청
+    #[error("only the owner can do this")]
+    NotRoomOwner,
+    /// `NEED_TWO_PLAYERS` - 두 좌석이 모두 차야 시작 가능
+    #[error("two seated players are required")]
+    NeedTwoPlayers,
+    /// `PLAYERS_NOT_READY` - 준비하지 않은 플레이어가 있음
+    #[error("not all players are ready")]
+    PlayersNotReady,
+    /// `GAME_NOT_STARTED` - 게임 중에만 가능한 요청
+    #[error("game has not started")]
+    GameNotStarted,
+    /// `GAME_NOT_IN_PROGRESS` - 진행 중이거나 끝난 게임만 리셋 가능
+    #[error("no game to reset")]
+    GameNotInProgress,
+    /// `GAME_IN_PROGRESS` - 게임 중에는 바꿀 수 없는 설정
+    #[error("cannot change this while playing")]
+    GameInProgress,
+    /// `GAME_NOT_FINISHED` - 게임이 끝난 뒤에만 가능한 요청 (재대결)
+    #[error("the game has not finished")]
+    GameNotFinished,
+    /// `NO_REMATCH_OFFER` - 수락할 상대의 재대결 제안이 없음
+    #[error("no rematch offer to accept")]
+    NoRematchOffer,
+    /// `NOT_YOUR_TURN` - 상대 턴에 굴림/이동/패스를 요청
+    #[error("not your turn")]
+    NotYourTurn,
+    /// `NOT_YOUR_SIDE` - 자신의 진영이 아닌 말을 이동
+    #[error("not your side")]
+    NotYourSide,
+    /// `INVALID_MOVE` - 세넷 규칙에 맞지 않는 이동
+    #[error("move violates the rules")]
+    InvalidMove,
+    /// `INVALID_ROLL` - 현재 굴림 값과 다른 값으로 패스
+    #[error("roll does not match the last roll")]
+    InvalidRoll,
+    /// `SPECTATOR_NOT_ALLOWED` - 관전자가 게임 진행 메시지를 보냄
+    #[error("spectators cannot take game actions")]
+    SpectatorNotAllowed,
+    /// `CHANNEL_NOT_ALLOWED` - 쓸 수 없는 채팅 채널
+    #[error("cannot post to channel {channel}")]
+    ChannelNotAllowed { channel: String },
+    /// `SERVER_DRAINING` - 서버가 종료/점검을 앞두고 있어 새 방이나 새 게임을 받지 않음
+    #[error("server is draining")]
+    ServerDraining,
+}
+
+impl ServerError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServerError::InvalidJson { .. } => "INVALID_JSON",
+            ServerError::InvalidMsgpack { .. } => "INVALID_MSGPACK",
+            ServerError::UnknownMessageType { .. } => "UNKNOWN_MESSAGE_TYPE",
+            ServerError::InvalidMessage { .. } => "INVALID_MESSAGE",
+            ServerError::UnsupportedProtocolVersion { .. } => "UNSUPPORTED_PROTOCOL_VERSION",
+            ServerError::HandshakeTooLate => "HANDSHAKE_TOO_LATE",
+            ServerError::RoomNotFound { .. } => "ROOM_NOT_FOUND",
+            ServerError::InvalidInviteCode { .. } => "INVALID_INVITE_CODE",
+            ServerError::InviteCodeRequired => "INVITE_CODE_REQUIRED",
+            ServerError::NotInRoom => "NOT_IN_ROOM",
+            ServerError::InvalidPassword => "INVALID_PASSWORD",
+            ServerError::InvalidSession => "INVALID_SESSION",
+            ServerError::TooManyPasswordAttempts { .. } => "TOO_MANY_PASSWORD_ATTEMPTS",
+            ServerError::RoomFull => "ROOM_FULL",
+            ServerError::SpectatorsFull => "SPECTATORS_FULL",
+            ServerError::SpectatingDisabled => "SPECTATING_DISABLED",
+            ServerError::AlreadyJoined => "ALREADY_JOINED",
+            ServerError::AlreadySpectating => "ALREADY_SPECTATING",
+            ServerError::NoAvailableSeats => "NO_AVAILABLE_SEATS",
+            ServerError::BannedFromRoom => "BANNED_FROM_ROOM",
+            ServerError::MemberNotFound { .. } => "MEMBER_NOT_FOUND",
+            ServerError::SeatTaken { .. } => "SEAT_TAKEN",
+            ServerError::NotAPlayer { .. } => "NOT_A_PLAYER",
+            ServerError::CannotKickSelf => "CANNOT_KICK_SELF",
+            ServerError::NotRoomOwner => "NOT_ROOM_OWNER",
+            ServerError::NeedTwoPlayers => "NEED_TWO_PLAYERS",
+            ServerError::PlayersNotReady => "PLAYERS_NOT_READY",
+            ServerError::GameNotStarted => "GAME_NOT_STARTED",
+            ServerError::GameNotInProgress => "GAME_NOT_IN_PROGRESS",
+            ServerError::GameInProgress => "GAME_IN_PROGRESS",
+            ServerError::GameNotFinished => "GAME_NOT_FINISHED",
+            ServerError::NoRematchOffer => "NO_REMATCH_OFFER",
+            ServerError::NotYourTurn => "NOT_YOUR_TURN",
+            ServerError::NotYourSide => "NOT_YOUR_SIDE",
+            ServerError::InvalidMove => "INVALID_MOVE",
+            ServerError::InvalidRoll => "INVALID_ROLL",
+            ServerError::SpectatorNotAllowed => "SPECTATOR_NOT_ALLOWED",
+            ServerError::ChannelNotAllowed { .. } => "CHANNEL_NOT_ALLOWED",
+            ServerError::ServerDraining => "SERVER_DRAINING",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ServerError::RoomNotFound { .. }
+            | ServerError::InvalidInviteCode { .. }
+            | ServerError::MemberNotFound { .. } => ErrorCategory::NotFound,
+            ServerError::RoomFull
+            | ServerError::SpectatorsFull
+            | ServerError::AlreadyJoined
+            | ServerError::AlreadySpectating
+            | ServerError::NeedTwoPlayers
+            | ServerError::PlayersNotReady
+            | ServerError::GameNotStarted
+            | ServerError::GameNotInProgress
+            | ServerError::GameInProgress
+            | ServerError::GameNotFinished
+            | ServerError::NoRematchOffer
+            | ServerError::NotYourTurn
+            | ServerError::SeatTaken { .. }
+            | ServerError::HandshakeTooLate => ErrorCategory::Conflict,
+            ServerError::TooManyPasswordAttempts { .. } => ErrorCategory::TooManyRequests,
+            ServerError::NoAvailableSeats => ErrorCategory::ServerError,
+            ServerError::ServerDraining => ErrorCategory::Unavailable,
+            _ => ErrorCategory::ClientError,
+        }
+    }
+
+    /// 에러 자체가 가진 구조화된 정보
+    pub fn details(&self) -> Value {
+        match self {
+            ServerError::InvalidJson { received } => json!({"received": received}),
+            ServerError::InvalidMsgpack { received_bytes } => {
+                json!({"receivedBytes": received_bytes})
+            }
+            ServerError::UnknownMessageType { received_type } => {
+                json!({"receivedType": received_type})
+            }
+            ServerError::InvalidMessage {
+                msg_type,
+                field,
+                reason,
+            } => json!({"receivedType": msg_type, "field": field, "reason": reason}),
+            ServerError::UnsupportedProtocolVersion { client_version } => json!({
+                "clientVersion": client_version,
+                "minVersion": protocol::MIN_PROTOCOL_VERSION,
+                "maxVersion": protocol::PROTOCOL_VERSION
+            }),
+            ServerError::RoomNotFound { room_id } => json!({"roomId": room_id}),
+            ServerError::InvalidInviteCode { invite_code } => json!({"inviteCode": invite_code}),
+            ServerError::TooManyPasswordAttempts { retry_after_secs } => {
+                json!({"retryAfterSecs": retry_after_secs})
+            }
+            ServerError::MemberNotFound { member_id } | ServerError::NotAPlayer { member_id } => {
+                json!({"memberId": member_id})
+            }
+            ServerError::SeatTaken { side } => json!({"side": side.to_string()}),
+            ServerError::InvalidMove => json!({"reason": "rule_violation"}),
+            ServerError::ChannelNotAllowed { channel } => json!({"channel": channel}),
+            _ => json!({}),
+        }
+    }
+
+    /// `context`(예: roomId)를 에러 정보와 합쳐서 `locale` 언어의 `ERROR` 메시지를 만듭니다.
+    pub fn to_msg(&self, locale: Locale, context: Value) -> ServerMsg {
+        let mut details = self.details();
+        if let (Some(d), Value::Object(ctx)) = (details.as_object_mut(), context) {
+            for (k, v) in ctx {
+                d.entry(k).or_insert(v);
+            }
+        }
+        ServerMsg::Error {
+            code: self.code().to_string(),
+            message: i18n::error_message(locale, self.code(), &details, &self.to_string()),
+            category: self.category(),
+            status: self.category().status(),
+            details,
+        }
+    }
+}
+
+#[cfg(test)]
+impl ServerError {
+    /// 모든 에러 종류를 하나씩. 에러를 새로 추가하면 아래 match가 컴파일되지 않으므로 여기에도 넣게 됩니다.
+    pub(crate) fn every_kind() -> Vec<ServerError> {
+        let all = vec![
+            ServerError::InvalidJson {
+                received: "x".into(),
+            },
+            ServerError::InvalidMsgpack { received_bytes: 1 },
+            ServerError::UnknownMessageType {
+                received_type: "FOO".into(),
+            },
+            ServerError::InvalidMessage {
+                msg_type: "CREATE_ROOM".into(),
+                field: "maxPlayers".into(),
+                reason: "invalid value".into(),
+            },
+            ServerError::UnsupportedProtocolVersion { client_version: 99 },
+            ServerError::HandshakeTooLate,
+            ServerError::RoomNotFound {
+                room_id: "r1".into(),
+            },
+            ServerError::InvalidInviteCode {
+                invite_code: "ABC234".into(),
+            },
+            ServerError::InviteCodeRequired,
+            ServerError::NotInRoom,
+            ServerError::InvalidPassword,
+            ServerError::TooManyPasswordAttempts {
+                retry_after_secs: 30,
+            },
+            ServerError::InvalidSession,
+            ServerError::RoomFull,
+            ServerError::SpectatorsFull,
+            ServerError::SpectatingDisabled,
+            ServerError::AlreadyJoined,
+            ServerError::AlreadySpectating,
+            ServerError::NoAvailableSeats,
+            ServerError::BannedFromRoom,
+            ServerError::MemberNotFound {
+                member_id: "p2".into(),
+            },
+            ServerError::SeatTaken { side: 'B' },
+            ServerError::NotAPlayer {
+                member_id: "s1".into(),
+            },
+            ServerError::CannotKickSelf,
+            ServerError::NotRoomOwner,
+            ServerError::NeedTwoPlayers,
+            ServerError::PlayersNotReady,
+            ServerError::GameNotStarted,
+            ServerError::GameNotInProgress,
+            ServerError::GameInProgress,
+            ServerError::GameNotFinished,
+            ServerError::NoRematchOffer,
+            ServerError::NotYourTurn,
+            ServerError::NotYourSide,
+            ServerError::InvalidMove,
+            ServerError::InvalidRoll,
+            ServerError::SpectatorNotAllowed,
+            ServerError::ChannelNotAllowed {
+                channel: "spectators".into(),
+            },
+            ServerError::ServerDraining,
+        ];
+        for e in &all {
+            match e {
+                ServerError::InvalidJson { .. }
+                | ServerError::InvalidMsgpack { .. }
+                | ServerError::UnknownMessageType { .. }
+                | ServerError::InvalidMessage { .. }
+                | ServerError::UnsupportedProtocolVersion { .. }
+                | ServerError::HandshakeTooLate
+                | ServerError::RoomNotFound { .. }
+                | ServerError::InvalidInviteCode { .. }
+                | ServerError::InviteCodeRequired
+                | ServerError::NotInRoom
+                | ServerError::InvalidPassword
+                | ServerError::TooManyPasswordAttempts { .. }
+                | ServerError::InvalidSession
+                | ServerError::RoomFull
+                | ServerError::SpectatorsFull
+                | ServerError::SpectatingDisabled
+                | ServerError::AlreadyJoined
+                | ServerError::AlreadySpectating
+                | ServerError::NoAvailableSeats
+                | ServerError::BannedFromRoom
+                | ServerError::MemberNotFound { .. }
+                | ServerError::SeatTaken { .. }
+                | ServerError::NotAPlayer { .. }
+                | ServerError::CannotKickSelf
+                | ServerError::NotRoomOwner
+                | ServerError::NeedTwoPlayers
+                | ServerError::PlayersNotReady
+                | ServerError::GameNotStarted
+                | ServerError::GameNotInProgress
+                | ServerError::GameInProgress
+                | ServerError::GameNotFinished
+                | ServerError::NoRematchOffer
+                | ServerError::NotYourTurn
+                | ServerError::NotYourSide
+                | ServerError::InvalidMove
+                | ServerError::InvalidRoll
+                | ServerError::SpectatorNotAllowed
+                | ServerError::ChannelNotAllowed { .. }
+                | ServerError::ServerDraining => {}
+            }
+        }
+        all
+    }
+}
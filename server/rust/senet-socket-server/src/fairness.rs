@@ -0,0 +1,175 @@
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// ========================= 굴림 공정성 =========================
+
+/// 네 개의 양면 막대를 던졌을 때 굴림 값별 확률: [1, 2, 3, 4, 5]
+/// 위로 향한 면이 k개일 확률은 C(4,k)/16이고, 0개는 5로 셉니다.
+pub const ROLL_PROBABILITIES: [f64; 5] =
+    [4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0, 1.0 / 16.0];
+
+/// 굴림 한 번의 기댓값 (37/16)
+pub const EXPECTED_MEAN: f64 = 37.0 / 16.0;
+
+/// 굴림 한 번의 분산: E[X²] - E[X]² = 105/16 - (37/16)²
+const VARIANCE: f64 = 105.0 / 16.0 - EXPECTED_MEAN * EXPECTED_MEAN;
+
+/// 이 유의수준보다 p값이 작으면 편향을 의심합니다.
+pub const SIGNIFICANCE: f64 = 0.01;
+
+/// 운 점수: 굴림 합계가 기댓값에서 표준편차 몇 개만큼 벗어났는지 (z 점수).
+/// 0이면 기대만큼, 양수면 기대보다 큰 값이 많이 나온 것입니다. 굴림이 없으면 0.
+pub fn luck_score(counts: &[u32; 5]) -> f64 {
+    let n: u32 = counts.iter().sum();
+    if n == 0 {
+        return 0.0;
+    }
+    let sum: f64 = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (i + 1) as f64 * c as f64)
+        .sum();
+    let n = n as f64;
+    (sum - n * EXPECTED_MEAN) / (VARIANCE * n).sqrt()
+}
+
+/// 서버가 만든 모든 게임 굴림(`GameState::roll`)의 값별 누적 횟수. 방 태스크들이 함께 씁니다.
+#[derive(Default)]
+pub struct RollAudit {
+    counts: [AtomicU64; 5],
+}
+
+impl RollAudit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, roll: u8) {
+        if (1..=5).contains(&roll) {
+            self.counts[roll as usize - 1].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn counts(&self) -> [u64; 5] {
+        std::array::from_fn(|i| self.counts[i].load(Ordering::Relaxed))
+    }
+
+    pub fn report(&self) -> FairnessReport {
+        FairnessReport::from_counts(self.counts())
+    }
+}
+
+/// 굴림 분포에 대한 카이제곱 적합도 검정 결과
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FairnessReport {
+    pub total_rolls: u64,
+    /// 굴림 값("1"~"5")별 실제 횟수
+    pub counts: BTreeMap<String, u64>,
+    /// 굴림 값별 기대 횟수
+    pub expected: BTreeMap<String, f64>,
+    pub mean_roll: f64,
+    pub expected_mean: f64,
+    pub chi_square: f64,
+    pub degrees_of_freedom: u32,
+    pub p_value: f64,
+    pub significance: f64,
+    /// p값이 유의수준 이상이면 편향의 증거가 없다고 봅니다.
+    pub unbiased: bool,
+}
+
+impl FairnessReport {
+    pub fn from_counts(counts: [u64; 5]) -> Self {
+        let total: u64 = counts.iter().sum();
+        let n = total as f64;
+        let mut chi_square = 0.0;
+        let mut weighted = 0.0;
+        for (i, &observed) in counts.iter().enumerate() {
+            let expected = n * ROLL_PROBABILITIES[i];
+            if expected > 0.0 {
+                chi_square += (observed as f64 - expected).powi(2) / expected;
+            }
+            weighted += (i + 1) as f64 * observed as f64;
+        }
+        let p_value = chi_square_sf_df4(chi_square);
+        let key = |i: usize| (i + 1).to_string();
+        FairnessReport {
+            total_rolls: total,
+            counts: (0..5).map(|i| (key(i), counts[i])).collect(),
+            expected: (0..5)
+                .map(|i| (key(i), n * ROLL_PROBABILITIES[i]))
+                .collect(),
+            mean_roll: if total == 0 { 0.0 } else { weighted / n },
+            expected_mean: EXPECTED_MEAN,
+            chi_square,
+            degrees_of_freedom: 4,
+            p_value,
+            significance: SIGNIFICANCE,
+            unbiased: p_value >= SIGNIFICANCE,
+        }
+    }
+}
+
+/// 자유도 4인 카이제곱 분포의 상위 꼬리 확률 P(X ≥ x) = e^(-x/2) · (1 + x/2)
+fn chi_square_sf_df4(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let h = x / 2.0;
+    (-h).exp() * (1.0 + h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Dice;
+
+    #[test]
+    fn chi_square_sf_df4_matches_table() {
+        assert_eq!(chi_square_sf_df4(0.0), 1.0);
+        // 자유도 4의 임계값: 13.28 → 0.01, 9.49 → 0.05, 7.78 → 0.10
+        assert!((chi_square_sf_df4(13.28) - 0.01).abs() < 1e-4);
+        assert!((chi_square_sf_df4(9.488) - 0.05).abs() < 1e-4);
+        assert!((chi_square_sf_df4(7.779) - 0.10).abs() < 1e-4);
+        assert!(chi_square_sf_df4(30.0) < chi_square_sf_df4(13.28));
+    }
+
+    #[test]
+    fn luck_score_is_zero_for_expected_rolls() {
+        // 16번 던질 때의 기대 분포 그대로
+        assert_eq!(luck_score(&[4, 6, 4, 1, 1]), 0.0);
+        assert_eq!(luck_score(&[40, 60, 40, 10, 10]), 0.0);
+        assert_eq!(luck_score(&[0; 5]), 0.0);
+        assert!(luck_score(&[0, 0, 0, 0, 16]) > 0.0);
+        assert!(luck_score(&[16, 0, 0, 0, 0]) < 0.0);
+    }
+
+    #[test]
+    fn seeded_dice_look_unbiased() {
+        let mut dice = Dice::new(47);
+        let audit = RollAudit::new();
+        for _ in 0..10_000 {
+            audit.record(dice.throw().0);
+        }
+        let report = audit.report();
+        assert_eq!(report.total_rolls, 10_000);
+        assert!(report.unbiased, "{:?}", report);
+        assert!(report.p_value >= SIGNIFICANCE);
+        assert!((report.mean_roll - EXPECTED_MEAN).abs() < 0.05);
+    }
+
+    #[test]
+    fn skewed_counts_are_flagged() {
+        let report = FairnessReport::from_counts([100, 100, 100, 100, 100]);
+        assert!(!report.unbiased);
+        assert!(report.p_value < SIGNIFICANCE);
+
+        let report = FairnessReport::from_counts([400, 600, 400, 100, 100]);
+        assert_eq!(report.chi_square, 0.0);
+        assert_eq!(report.p_value, 1.0);
+        assert!(report.unbiased);
+    }
+}
@@ -1,4 +1,5 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -11,6 +12,91 @@ pub const WATER_SQUARE: u8 = 27;
 pub const EXIT_SQUARE: u8 = 30;
 pub const PIECES: usize = 5;
 
+/// 막대 4개를 던집니다. 평평한 면이 위로 온 개수가 값이고, 0개면 5로 칩니다.
+pub fn throw_sticks(rng: &mut impl Rng) -> (u8, [u8; 4]) {
+    let mut faces = [0u8; 4];
+    for slot in faces.iter_mut() {
+        *slot = rng.gen_bool(0.5) as u8;
+    }
+    let up: u8 = faces.iter().sum();
+    (if up == 0 { 5 } else { up }, faces)
+}
+
+/// 한 게임의 막대 던지기 난수. 시드와 던진 횟수만 있으면 같은 상태로 되살릴 수 있으므로
+/// 직렬화할 때는 그 둘만 남깁니다 (서버 재시작 후 복원용).
+/// `StdRng`는 rand 버전에 따라 알고리즘이 바뀔 수 있어, 저장된 시드가 계속 같은 굴림을 내도록 ChaCha20으로 고정합니다.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "DiceState", into = "DiceState")]
+pub struct Dice {
+    seed: u64,
+    throws: u64,
+    rng: ChaCha20Rng,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiceState {
+    seed: u64,
+    throws: u64,
+}
+
+impl Dice {
+    pub fn new(seed: u64) -> Self {
+        Dice {
+            seed,
+            throws: 0,
+            rng: ChaCha20Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn throw(&mut self) -> (u8, [u8; 4]) {
+        self.throws += 1;
+        throw_sticks(&mut self.rng)
+    }
+}
+
+impl From<DiceState> for Dice {
+    fn from(state: DiceState) -> Self {
+        let mut dice = Dice::new(state.seed);
+        for _ in 0..state.throws {
+            dice.throw();
+        }
+        dice
+    }
+}
+
+impl From<Dice> for DiceState {
+    fn from(dice: Dice) -> Self {
+        DiceState {
+            seed: dice.seed,
+            throws: dice.throws,
+        }
+    }
+}
+
+/// 선공을 정하는 시작 굴림 한 번
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OpeningThrow {
+    pub side: char,
+    pub roll: u8,
+}
+
+/// W부터 번갈아 던져서 먼저 1이 나온 쪽이 선공입니다. 선공 진영과 던진 기록을 돌려줍니다.
+pub fn opening_throw(dice: &mut Dice) -> (char, Vec<OpeningThrow>) {
+    let mut throws = vec![];
+    for side in ['W', 'B'].into_iter().cycle() {
+        let (roll, _) = dice.throw();
+        throws.push(OpeningThrow { side, roll });
+        if roll == 1 {
+            return (side, throws);
+        }
+    }
+    unreachable!("cycle()은 끝나지 않음")
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameSnapshot {
     pub pieces: HashMap<char, Vec<u8>>, // 'W'/'B' -> 각 말 위치(0=off, 30=exit는 별도 처리)
@@ -20,7 +106,7 @@ pub struct GameSnapshot {
     pub last_move: Option<Value>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub turn: char, // 'W' or 'B'
     pub last_roll: Option<u8>,
@@ -28,16 +114,29 @@ pub struct GameState {
     pub w: [u8; PIECES],
     pub b: [u8; PIECES],
     pub game_over: bool,
+    /// 이 게임의 굴림 난수. 같은 시드와 수순이면 같은 굴림이 나옵니다.
+    pub dice: Dice,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameState {
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
         let mut g = Self {
             turn: 'W',
             last_roll: None,
             w: [0; PIECES],
             b: [0; PIECES],
             game_over: false,
+            dice: Dice::new(seed),
         };
         // 초기 배치: W=1,3,5,7,9 / B=2,4,6,8,10
         for i in 0..PIECES {
@@ -159,14 +258,7 @@ impl GameState {
 
     pub fn roll(&mut self) -> (u8, [u8; 4], bool, bool) {
         // returns: roll, faces, grants_extra_turn_default, can_move
-        let mut faces = 0u8;
-        let mut vec = [0u8; 4];
-        for i in 0..4 {
-            let face = rand::thread_rng().gen_bool(0.5) as u8;
-            vec[i] = face;
-            faces += face;
-        }
-        let roll = if faces == 0 { 5 } else { faces };
+        let (roll, vec) = self.dice.throw();
         self.last_roll = Some(roll);
         let legal = self.legal_moves(self.turn, roll);
         let grants = roll == 4 || roll == 5;
@@ -309,3 +401,16 @@ impl GameState {
         (true, extra && !self.game_over, passes_water, captured)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_throws_stay_the_same() {
+        // 게임 기록의 시드로 굴림을 다시 만들 수 있어야 하므로 값 자체를 고정
+        let mut dice = Dice::new(7);
+        let rolls: Vec<u8> = (0..12).map(|_| dice.throw().0).collect();
+        assert_eq!(rolls, [2, 3, 1, 5, 1, 3, 2, 3, 2, 2, 2, 1]);
+    }
+}
@@ -1,52 +1,118 @@
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Query, State,
     },
+    http::{header, HeaderMap},
     response::IntoResponse,
 };
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    messages::ServerMsg,
+    error::ServerError,
+    i18n::Locale,
+    lobby::spawn_lobby_subscription,
+    messages::{ClientMsg, ServerMsg},
+    password::{self, AttemptLimiter},
+    protocol::{self, Outgoing, Session, WireFormat},
     room::{
-        create_room, delete_room, get_room_list, join_room, leave_room, reset_game, start_game,
+        accept_rematch, choose_seat, create_room, delete_room, get_room_list, heartbeat, join_room,
+        kick_player, leave_room, leave_spectator, move_piece, offer_rematch, pass_turn,
+        regenerate_invite_code, reset_game, resume_session, roll_sticks, send_chat,
+        set_chat_policy, set_ready, set_spectator_policy, spectate_room, start_game, swap_sides,
+        transfer_ownership, update_room_settings, RoomSettingsUpdate, Subscription,
     },
-    types::{get_str, ts, AppState, Room, RoomStatus},
+    shutdown::DrainPhase,
+    types::{AppState, Room, RoomRef, SpectatorDelay, SpectatorPolicy, DEFAULT_MAX_SPECTATORS},
 };
 
 // ========================= WebSocket 핸들러 =========================
 
-pub async fn ws_handler(State(state): State<AppState>, ws: WebSocketUpgrade) -> impl IntoResponse {
-    info!("🔌 새로운 WebSocket 연결 요청");
-    ws.on_upgrade(move |socket| client_loop(state, socket))
+pub async fn ws_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    // 표시 언어: ?locale= 쿼리 > Accept-Language 헤더 > 영어
+    let locale = params
+        .get("locale")
+        .and_then(|l| Locale::from_tag(l))
+        .or_else(|| {
+            headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(Locale::negotiate)
+        })
+        .unwrap_or_default();
+    info!("🔌 새로운 WebSocket 연결 요청 (언어: {})", locale.as_str());
+    ws.max_message_size(protocol::MAX_MESSAGE_BYTES)
+        .protocols(protocol::SUBPROTOCOLS)
+        .on_upgrade(move |socket| client_loop(state, socket, locale))
 }
 
 // ========================= 클라이언트 루프 =========================
 
-async fn client_loop(state: AppState, socket: WebSocket) {
-    info!("🔄 클라이언트 루프 시작");
+async fn client_loop(state: AppState, socket: WebSocket, mut locale: Locale) {
+    // 서브프로토콜로 고른 전송 인코딩 (없으면 JSON 텍스트)
+    let format = WireFormat::from_subprotocol(socket.protocol().and_then(|p| p.to_str().ok()));
+    info!("🔄 클라이언트 루프 시작 (인코딩: {})", format.as_str());
 
     // 개인 sender - 버퍼 크기를 늘려서 메시지 손실 방지
-    let (tx, mut rx) = mpsc::channel::<String>(1024);
+    let (tx, mut rx) = mpsc::channel::<Arc<Outgoing>>(protocol::SEND_QUEUE_SIZE);
+    // HELLO로 언어가 바뀌면 전송 태스크에도 알려줌
+    let (locale_tx, locale_rx) = watch::channel(locale);
     let (mut ws_tx, mut ws_rx) = socket.split();
+    // 드레인 단계 변화 - 드레인 중에 들어온 연결에도 바로 종료 예고를 보냄
+    let mut drain_rx = state.drain.subscribe();
+    drain_rx.mark_changed();
 
-    // WebSocket 전송 태스크
+    // WebSocket 전송 태스크 - 연결의 언어와 인코딩으로 직렬화
     let _send_task = tokio::spawn(async move {
         let mut message_count = 0;
-        while let Some(msg) = rx.recv().await {
+        let mut was_draining = false;
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                Ok(()) = drain_rx.changed() => {
+                    let phase = *drain_rx.borrow_and_update();
+                    if let DrainPhase::Closed { .. } = phase {
+                        let close = CloseFrame {
+                            code: close_code::AWAY,
+                            reason: "server shutting down".into(),
+                        };
+                        ws_tx.send(Message::Close(Some(close))).await.ok();
+                        info!("🚪 드레인 - 연결을 닫습니다");
+                        break;
+                    }
+                    let notice = phase.notice(was_draining);
+                    was_draining = matches!(phase, DrainPhase::Draining { .. });
+                    match notice {
+                        Some(notice) => Outgoing::new(notice),
+                        None => continue,
+                    }
+                }
+            };
             message_count += 1;
+            // 방 브로드캐스트는 다른 구독자가 이미 직렬화해 둔 프레임을 재사용
+            let frame = msg.frame(format, *locale_rx.borrow());
             debug!(
                 "📤 클라이언트에게 메시지 전송 #{}: {} bytes",
                 message_count,
-                msg.len()
+                frame.byte_len()
             );
 
-            match ws_tx.send(Message::Text(msg)).await {
+            match ws_tx.send(frame.to_message()).await {
                 Ok(_) => {
                     debug!("✅ 메시지 전송 성공 #{}", message_count);
                 }
@@ -59,74 +125,172 @@ async fn client_loop(state: AppState, socket: WebSocket) {
         info!("🔚 전송 태스크 종료 - 총 {} 개 메시지 전송", message_count);
     });
 
+    // HELLO로 협상한 프로토콜 정보 (HELLO 없이 시작한 예전 클라이언트는 최소 버전)
+    let mut session = Session::default();
+
     // 조인한 방
     let mut joined_room: Option<Arc<Room>> = None;
     let mut self_player_id: Option<String> = None;
-    let mut self_player_name: Option<String> = None;
+    // 관전자로 입장했는지 여부 (관전자는 게임 진행 메시지를 보낼 수 없음)
+    let mut is_spectator = false;
+    // 로비 구독 태스크 (SUBSCRIBE_LOBBY)
+    let mut lobby_task: Option<JoinHandle<()>> = None;
+    // 이 연결의 방 비밀번호 실패 횟수
+    let mut password_attempts = AttemptLimiter::new(password::CONNECTION_MAX_FAILURES);
+    // 방장이 이 연결을 내보낸 방 ID (브로드캐스트 리스너가 알려줌)
+    let (removed_tx, mut removed_rx) = mpsc::channel::<String>(4);
 
     // 메시지 수신 루프
     let mut message_count = 0;
-    while let Some(Ok(Message::Text(text))) = ws_rx.next().await {
-        message_count += 1;
-        debug!(
-            "📨 클라이언트로부터 메시지 수신 #{}: {}",
-            message_count, text
-        );
+    while let Some(Ok(frame)) = ws_rx.next().await {
+        // 텍스트는 JSON, 바이너리는 MessagePack (협상한 인코딩과 무관하게 둘 다 받음)
+        let parsed = match frame {
+            Message::Text(text) => {
+                message_count += 1;
+                // 원문에는 비밀번호가 들어 있을 수 있으므로 크기만 남김
+                debug!(
+                    "📨 클라이언트로부터 메시지 수신 #{}: {} bytes",
+                    message_count,
+                    text.len()
+                );
+                ClientMsg::parse(&text)
+            }
+            Message::Binary(bytes) => {
+                message_count += 1;
+                debug!(
+                    "📨 클라이언트로부터 바이너리 메시지 수신 #{}: {} bytes",
+                    message_count,
+                    bytes.len()
+                );
+                ClientMsg::parse_msgpack(&bytes)
+            }
+            Message::Close(_) => break,
+            // Ping/Pong은 axum이 처리
+            _ => continue,
+        };
 
-        let Ok(v): Result<Value, _> = serde_json::from_str(&text) else {
-            warn!("⚠️ 잘못된 JSON 형식의 메시지: {}", text);
-            send_err(
-                &tx,
-                "INVALID_JSON",
-                "잘못된 JSON 형식입니다",
-                json!({"received": text}),
-            )
-            .await;
-            continue;
+        let msg = match parsed {
+            Ok(msg) => msg,
+            Err(e) => {
+                // details에는 원문이 들어 있을 수 있으므로 로그에는 남기지 않음
+                warn!("⚠️ 잘못된 메시지: {} ({})", e, e.code());
+                send_err(&tx, locale, e, json!({})).await;
+                continue;
+            }
         };
 
-        let t = v
-            .get("type")
-            .and_then(|x| x.as_str())
-            .unwrap_or("")
-            .to_string();
-        let data = v.get("data").cloned().unwrap_or(json!({}));
+        debug!("🔍 메시지: {:?}", msg);
+
+        // 강퇴당한 방이면 방 상태를 비움 (구독은 리스너가 이미 닫음)
+        while let Ok(rid) = removed_rx.try_recv() {
+            if joined_room.as_ref().is_some_and(|room| room.id == rid) {
+                info!("🚫 방에서 내보내짐: {}", rid);
+                joined_room = None;
+                is_spectator = false;
+            }
+        }
+
+        // 관전자는 준비/시작/굴림/이동/패스/리셋을 할 수 없음
+        if is_spectator && msg.is_game_action() {
+            send_err(&tx, locale, ServerError::SpectatorNotAllowed, json!({})).await;
+            continue;
+        }
+
+        match msg {
+            // ---------- HELLO ----------
+            ClientMsg::Hello {
+                protocol_version,
+                features,
+                locale: requested_locale,
+            } => {
+                if session.negotiated || joined_room.is_some() {
+                    send_err(&tx, locale, ServerError::HandshakeTooLate, json!({})).await;
+                    continue;
+                }
+                session = match protocol::negotiate(protocol_version) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        // 호환되지 않는 클라이언트는 방 메시지를 주고받기 전에 끊음
+                        warn!("🚫 프로토콜 버전 불일치: {}", e);
+                        send_err(&tx, locale, e, json!({})).await;
+                        break;
+                    }
+                };
+                if let Some(l) = requested_locale.as_deref().and_then(Locale::from_tag) {
+                    locale = l;
+                    locale_tx.send(l).ok();
+                }
+                let server_features = protocol::server_features(format);
+                info!(
+                    "🤝 프로토콜 협상 완료 - 버전: {}, 클라이언트 기능: {:?}, 서버 기능: {:?}",
+                    session.protocol_version, features, server_features
+                );
 
-        debug!("🔍 메시지 타입: {}, 데이터: {:?}", t, data);
+                let welcome = ServerMsg::Welcome {
+                    protocol_version: session.protocol_version,
+                    min_protocol_version: protocol::MIN_PROTOCOL_VERSION,
+                    server_version: env!("CARGO_PKG_VERSION").to_string(),
+                    features: server_features,
+                    locale: locale.as_str().to_string(),
+                    limits: protocol::server_limits(),
+                };
+                if let Err(e) = tx.send(Outgoing::new(welcome)).await {
+                    error!("❌ WELCOME 전송 실패: {}", e);
+                }
+            }
 
-        match t.as_str() {
             // ---------- GET_ROOM_LIST ----------
-            "GET_ROOM_LIST" => {
-                let filters = data.get("filters").cloned().unwrap_or(json!({}));
-                let response = get_room_list(&state, filters).await;
-                if let Err(e) = tx.send(response.wrap()).await {
-                    error!("❌ ROOM_LIST 전송 실패: {}", e);
+            ClientMsg::GetRoomList { filters } => match get_room_list(&state, filters) {
+                Ok(response) => {
+                    if let Err(e) = tx.send(Outgoing::new(response)).await {
+                        error!("❌ ROOM_LIST 전송 실패: {}", e);
+                    }
+                }
+                Err(e) => send_err(&tx, locale, e, json!({})).await,
+            },
+
+            // ---------- SUBSCRIBE_LOBBY ----------
+            ClientMsg::SubscribeLobby { filters } => {
+                // 다시 구독하면 새 필터로 처음부터
+                if let Some(task) = lobby_task.take() {
+                    task.abort();
+                }
+                lobby_task = Some(spawn_lobby_subscription(
+                    state.lobby.clone(),
+                    filters,
+                    tx.clone(),
+                ));
+            }
+
+            // ---------- UNSUBSCRIBE_LOBBY ----------
+            ClientMsg::UnsubscribeLobby {} => {
+                if let Some(task) = lobby_task.take() {
+                    task.abort();
+                    info!("📋 로비 구독 해제");
                 }
             }
 
             // ---------- CREATE_ROOM ----------
-            "CREATE_ROOM" => {
-                let room_name = data
-                    .get("roomName")
-                    .and_then(|x| x.as_str())
-                    .unwrap_or("Room")
-                    .to_string();
-                let password = data
-                    .get("password")
-                    .and_then(|x| x.as_str())
+            ClientMsg::CreateRoom {
+                room_name,
+                password,
+                max_players,
+                allow_spectators,
+                max_spectators,
+                spectator_delay,
+                variant,
+                visibility,
+                player_name,
+                player_id,
+            } => {
+                let password = password.filter(|s| !s.is_empty());
+                let spectator_policy = SpectatorPolicy {
+                    allow: allow_spectators.unwrap_or(true),
+                    max: max_spectators.unwrap_or(DEFAULT_MAX_SPECTATORS),
+                    delay: spectator_delay.unwrap_or(SpectatorDelay::Off),
+                };
+                let player_id = player_id
                     .filter(|s| !s.is_empty())
-                    .map(|s| s.to_string());
-                let max_players =
-                    data.get("maxPlayers").and_then(|x| x.as_u64()).unwrap_or(2) as usize;
-                let player_name = data
-                    .get("playerName")
-                    .and_then(|x| x.as_str())
-                    .unwrap_or("Player")
-                    .to_string();
-                let player_id = data
-                    .get("playerId")
-                    .and_then(|x| x.as_str())
-                    .map(|s| s.to_string())
                     .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
                 let room_name_clone = room_name.clone();
@@ -141,827 +305,899 @@ async fn client_loop(state: AppState, socket: WebSocket) {
                     room_name,
                     password,
                     max_players,
-                    player_name.clone(),
+                    spectator_policy,
+                    variant,
+                    visibility,
+                    player_name,
                     player_id.clone(),
                 )
                 .await
                 {
-                    Ok(room) => {
+                    Ok((room, subscription)) => {
                         info!("✅ 방 생성 성공: {} (ID: {})", room_name_clone, room.id);
 
                         // 브로드캐스트 포워딩 - 해당 클라이언트만 메시지를 받도록
-                        let mut brx = room.tx.subscribe();
-                        let tx_clone = tx.clone();
-                        let player_id_clone = player_id.clone();
-                        let room_id_clone = room.id.clone();
-
-                        tokio::spawn(async move {
-                            info!(
-                                "🎯 브로드캐스트 리스너 시작 - 방: {}, 플레이어: {}",
-                                room_id_clone, player_id_clone
-                            );
-                            let mut msg_count = 0;
-
-                            while let Ok(msg) = brx.recv().await {
-                                msg_count += 1;
-                                debug!(
-                                    "📨 브로드캐스트 메시지 수신 #{} (방: {}, 플레이어: {}): {:?}",
-                                    msg_count, room_id_clone, player_id_clone, msg
-                                );
-
-                                let wrapped_msg = msg.wrap();
-                                debug!("📦 래핑된 메시지 길이: {} bytes", wrapped_msg.len());
-
-                                // 해당 클라이언트에게 메시지 전달
-                                match tx_clone.send(wrapped_msg).await {
-                                    Ok(_) => debug!("✅ 클라이언트에게 메시지 전달 성공 #{} (방: {}, 플레이어: {})", 
-                                                     msg_count, room_id_clone, player_id_clone),
-                                    Err(e) => {
-                                        error!("❌ 클라이언트에게 메시지 전달 실패 #{} (방: {}, 플레이어: {}): {:?}", 
-                                                msg_count, room_id_clone, player_id_clone, e);
-                                        break; // 전송 실패 시 리스너 종료
-                                    }
-                                }
-                            }
-                            info!(
-                                "🔚 브로드캐스트 리스너 종료 - 방: {}, 플레이어: {}, 총 메시지: {}",
-                                room_id_clone, player_id_clone, msg_count
-                            );
-                        });
+                        spawn_broadcast_forwarder(
+                            &room,
+                            subscription,
+                            tx.clone(),
+                            removed_tx.clone(),
+                            player_id.clone(),
+                        );
 
                         joined_room = Some(room);
                         self_player_id = Some(player_id);
-                        self_player_name = Some(player_name);
+                        is_spectator = false;
                     }
                     Err(e) => {
                         error!("❌ 방 생성 실패: {}", e);
-                        send_err(&tx, "ROOM_CREATION_FAILED", &e, json!({})).await;
+                        send_err(&tx, locale, e, json!({})).await;
                     }
                 }
             }
 
             // ---------- JOIN_ROOM ----------
-            "JOIN_ROOM" => {
-                let room_id = get_str(&data, "roomId");
-                let password = data.get("password").and_then(|x| x.as_str());
-                let player_name = get_str(&data, "playerName");
-                let player_id = get_str(&data, "playerId");
-
-                info!("🚪 방 참가 요청: {} (플레이어: {})", room_id, player_name);
+            ClientMsg::JoinRoom {
+                room_id,
+                invite_code,
+                password,
+                player_name,
+                player_id,
+            } => {
+                let target = match RoomRef::from_fields("JOIN_ROOM", room_id, invite_code) {
+                    Ok(target) => target,
+                    Err(e) => {
+                        send_err(&tx, locale, e, json!({})).await;
+                        continue;
+                    }
+                };
+                info!("🚪 방 참가 요청: {:?} (플레이어: {})", target, player_name);
+                if let Err(e) = password_attempts.check() {
+                    send_err(&tx, locale, e, target.context()).await;
+                    continue;
+                }
 
                 match join_room(
                     &state,
                     tx.clone(),
-                    room_id.clone(),
+                    target.clone(),
                     password,
                     player_name.clone(),
                     player_id.clone(),
                 )
                 .await
                 {
-                    Ok(room) => {
-                        info!("✅ 방 참가 성공: {} (플레이어: {})", room_id, player_name);
+                    Ok((room, subscription)) => {
+                        info!("✅ 방 참가 성공: {} (플레이어: {})", room.id, player_name);
 
                         // 브로드캐스트 포워딩 - 해당 클라이언트만 메시지를 받도록
-                        let mut brx = room.tx.subscribe();
-                        let tx_clone = tx.clone();
-                        let player_id_clone = player_id.clone();
-                        let room_id_clone = room.id.clone();
-
-                        tokio::spawn(async move {
-                            info!(
-                                "🎯 브로드캐스트 리스너 시작 - 방: {}, 플레이어: {}",
-                                room_id_clone, player_id_clone
-                            );
-                            let mut msg_count = 0;
-
-                            while let Ok(msg) = brx.recv().await {
-                                msg_count += 1;
-                                debug!(
-                                    "📨 브로드캐스트 메시지 수신 #{} (방: {}, 플레이어: {}): {:?}",
-                                    msg_count, room_id_clone, player_id_clone, msg
-                                );
-
-                                let wrapped_msg = msg.wrap();
-                                debug!("📦 래핑된 메시지 길이: {} bytes", wrapped_msg.len());
-
-                                // 해당 클라이언트에게 메시지 전달
-                                match tx_clone.send(wrapped_msg).await {
-                                    Ok(_) => debug!("✅ 클라이언트에게 메시지 전달 성공 #{} (방: {}, 플레이어: {})", 
-                                                     msg_count, room_id_clone, player_id_clone),
-                                    Err(e) => {
-                                        error!("❌ 클라이언트에게 메시지 전달 실패 #{} (방: {}, 플레이어: {}): {:?}", 
-                                                msg_count, room_id_clone, player_id_clone, e);
-                                        break; // 전송 실패 시 리스너 종료
-                                    }
-                                }
-                            }
-                            info!(
-                                "🔚 브로드캐스트 리스너 종료 - 방: {}, 플레이어: {}, 총 메시지: {}",
-                                room_id_clone, player_id_clone, msg_count
-                            );
-                        });
+                        spawn_broadcast_forwarder(
+                            &room,
+                            subscription,
+                            tx.clone(),
+                            removed_tx.clone(),
+                            player_id.clone(),
+                        );
 
                         joined_room = Some(room);
                         self_player_id = Some(player_id);
-                        self_player_name = Some(player_name);
+                        is_spectator = false;
+                    }
+                    Err(e) => {
+                        error!("❌ 방 참가 실패: {} (방: {:?})", e, target);
+                        if matches!(e, ServerError::InvalidPassword) {
+                            password_attempts.record_failure();
+                        }
+                        send_err(&tx, locale, e, target.context()).await;
+                    }
+                }
+            }
+
+            // ---------- SPECTATE_ROOM ----------
+            ClientMsg::SpectateRoom {
+                room_id,
+                invite_code,
+                password,
+                player_name,
+                player_id,
+            } => {
+                let target = match RoomRef::from_fields("SPECTATE_ROOM", room_id, invite_code) {
+                    Ok(target) => target,
+                    Err(e) => {
+                        send_err(&tx, locale, e, json!({})).await;
+                        continue;
+                    }
+                };
+                let spectator_name = player_name.unwrap_or_else(|| "Spectator".to_string());
+                let spectator_id = player_id
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+                info!("👀 관전 요청: {:?} (관전자: {})", target, spectator_name);
+                if let Err(e) = password_attempts.check() {
+                    send_err(&tx, locale, e, target.context()).await;
+                    continue;
+                }
+
+                match spectate_room(
+                    &state,
+                    tx.clone(),
+                    target.clone(),
+                    password,
+                    spectator_name.clone(),
+                    spectator_id.clone(),
+                )
+                .await
+                {
+                    Ok((room, subscription)) => {
+                        info!("✅ 관전 시작: {} (관전자: {})", room.id, spectator_name);
+
+                        // 관전자 구독은 지연 중계가 적용되는 관전자 채널
+                        spawn_broadcast_forwarder(
+                            &room,
+                            subscription,
+                            tx.clone(),
+                            removed_tx.clone(),
+                            spectator_id.clone(),
+                        );
+
+                        joined_room = Some(room);
+                        self_player_id = Some(spectator_id);
+                        is_spectator = true;
                     }
                     Err(e) => {
-                        error!("❌ 방 참가 실패: {} (방: {})", e, room_id);
-                        send_err(&tx, "JOIN_ROOM_FAILED", &e, json!({"roomId":room_id})).await;
+                        error!("❌ 관전 실패: {} (방: {:?})", e, target);
+                        if matches!(e, ServerError::InvalidPassword) {
+                            password_attempts.record_failure();
+                        }
+                        send_err(&tx, locale, e, target.context()).await;
+                    }
+                }
+            }
+
+            // ---------- SET_SPECTATOR_POLICY ----------
+            ClientMsg::SetSpectatorPolicy {
+                allow_spectators,
+                max_spectators,
+                spectator_delay,
+            } => {
+                if let (Some(room), Some(player_id)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = set_spectator_policy(
+                        &room,
+                        player_id.clone(),
+                        allow_spectators,
+                        max_spectators,
+                        spectator_delay,
+                    )
+                    .await
+                    {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
                 }
             }
 
             // ---------- READY_STATUS ----------
-            "READY_STATUS" => {
+            ClientMsg::ReadyStatus {
+                room_id: rid,
+                player_id: pid,
+                is_ready,
+            } => {
                 if let Some(room) = &joined_room {
-                    let room = room.clone();
-                    let pid = get_str(&data, "playerId");
-                    let rid = get_str(&data, "roomId");
-                    let is_ready = data
-                        .get("isReady")
-                        .and_then(|x| x.as_bool())
-                        .unwrap_or(false);
-                    let mut inner = room.inner.write().await;
                     if room.id != rid {
                         continue;
                     }
-                    inner.ready.insert(pid.clone(), is_ready);
-                    let all_ready = inner.players.len() == 2
-                        && inner.players.iter().all(|p| {
-                            inner
-                                .ready
-                                .get(p.key())
-                                .map(|r| *r.value())
-                                .unwrap_or(false)
-                        });
-                    room.tx
-                        .send(ServerMsg::PlayerReady {
-                            room_id: room.id.clone(),
-                            player_id: pid,
-                            is_ready: is_ready,
-                            all_ready: all_ready,
-                        })
-                        .ok();
-                    inner.last_activity = ts();
+                    set_ready(room, pid, is_ready).await;
                 }
             }
 
             // ---------- START_GAME ----------
-            "START_GAME" => {
+            ClientMsg::StartGame { player_id: pid } => {
                 if let Some(room) = &joined_room {
                     let room = room.clone();
-                    let pid = get_str(&data, "playerId");
 
                     match start_game(&room, pid.clone()).await {
                         Ok(_) => {
                             // 게임 시작 성공
                         }
                         Err(e) => {
-                            send_err(&tx, "START_GAME_FAILED", &e, json!({"roomId":room.id})).await;
+                            send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                         }
                     }
                 }
             }
 
             // ---------- ROLL_STICKS ----------
-            "ROLL_STICKS" => {
-                debug!("🎲 ROLL_STICKS 메시지 수신: {:?}", data);
+            ClientMsg::RollSticks {
+                room_id: rid,
+                player_id: pid,
+            } => {
+                if let Some(room) = &joined_room {
+                    let room = room.clone();
+                    if room.id != rid {
+                        continue;
+                    }
 
-                // 연결 상태 상세 디버깅
-                debug!("🔍 연결 상태 디버깅:");
-                debug!(
-                    "  - joined_room 상태: {}",
-                    if joined_room.is_some() {
-                        "있음"
-                    } else {
-                        "없음"
+                    if let Err(e) = roll_sticks(&room, pid).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
-                );
-                if let Some(ref room) = joined_room {
-                    debug!("  - 현재 방 ID: {}", room.id);
                 }
-                debug!("  - self_player_id: {:?}", self_player_id);
-                debug!("  - self_player_name: {:?}", self_player_name);
-
-                // 🔄 재연결 자동 복구: joined_room이 없지만 요청에 방 정보가 있다면 복구 시도
-                if joined_room.is_none() {
-                    let rid = get_str(&data, "roomId");
-                    let pid = get_str(&data, "playerId");
-
-                    if !rid.is_empty() && !pid.is_empty() {
-                        info!(
-                            "🔄 재연결 감지 - 방 복구 시도: 방ID={}, 플레이어ID={}",
-                            rid, pid
-                        );
+            }
 
-                        if let Some(room) = state.rooms.get(&rid) {
-                            let inner = room.inner.try_read();
-                            if let Ok(inner) = inner {
-                                if inner.players.contains_key(&pid) {
-                                    // 플레이어 이름을 먼저 저장
-                                    let player_name = inner
-                                        .players
-                                        .get(&pid)
-                                        .map(|p| p.name.clone())
-                                        .unwrap_or_else(|| pid.clone());
-                                    
-                                    info!(
-                                        "✅ 방 복구 성공: 플레이어 '{}' 를 방 '{}' 에 다시 연결",
-                                        pid, rid
-                                    );
-                                    drop(inner); // 읽기 잠금 해제
-
-                                    // 브로드캐스트 리스너 다시 시작
-                                    let tx_clone = tx.clone();
-                                    let room_id_clone = rid.clone();
-                                    let player_id_clone = pid.clone();
-                                    let broadcast_rx = room.tx.subscribe();
-                                    tokio::spawn(async move {
-                                        let mut msg_count = 0;
-                                        let mut broadcast_rx = broadcast_rx;
-                                        info!(
-                                            "🎯 브로드캐스트 리스너 재시작 - 방: {}, 플레이어: {}",
-                                            room_id_clone, player_id_clone
-                                        );
-
-                                        while let Ok(msg) = broadcast_rx.recv().await {
-                                            msg_count += 1;
-                                            debug!("📨 브로드캐스트 메시지 수신 #{} (방: {}, 플레이어: {}): {:?}", 
-                                                    msg_count, room_id_clone, player_id_clone, msg);
-
-                                            let wrapped_msg = msg.wrap();
-                                            debug!(
-                                                "📦 래핑된 메시지 길이: {} bytes",
-                                                wrapped_msg.len()
-                                            );
-
-                                            if tx_clone.send(wrapped_msg).await.is_err() {
-                                                error!("❌ 클라이언트 연결 끊어짐 #{} (방: {}, 플레이어: {})", 
-                                                        msg_count, room_id_clone, player_id_clone);
-                                                break;
-                                            } else {
-                                                debug!("✅ 클라이언트에게 메시지 전달 성공 #{} (방: {}, 플레이어: {})", 
-                                                        msg_count, room_id_clone, player_id_clone);
-                                            }
-                                        }
-                                        info!("🔚 브로드캐스트 리스너 종료 - 방: {}, 플레이어: {}, 총 메시지: {}", 
-                                                room_id_clone, player_id_clone, msg_count);
-                                    });
-
-                                    // 방 상태 복구
-                                    joined_room = Some(room.clone());
-                                    self_player_id = Some(pid.clone());
-                                    self_player_name = Some(player_name);
-
-                                    info!("🔄 방 상태 복구 완료");
-                                } else {
-                                    warn!("❌ 방 복구 실패: 플레이어 '{}' 를 방 '{}' 에서 찾을 수 없음", pid, rid);
-                                }
-                            }
-                        } else {
-                            warn!("❌ 방 복구 실패: 방 '{}' 를 찾을 수 없음", rid);
-                        }
+            // ---------- MOVE_PIECE ----------
+            ClientMsg::MovePiece {
+                room_id: rid,
+                player_id: pid,
+                move_,
+            } => {
+                if let Some(room) = &joined_room {
+                    let room = room.clone();
+                    if room.id != rid {
+                        continue;
+                    }
+                    if let Err(e) = move_piece(&room, pid, move_).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
                 }
+            }
 
+            // ---------- PASS_TURN ----------
+            ClientMsg::PassTurn {
+                room_id: rid,
+                player_id: pid,
+                roll: requested_roll,
+            } => {
                 if let Some(room) = &joined_room {
                     let room = room.clone();
-                    let pid = get_str(&data, "playerId");
-                    let rid = get_str(&data, "roomId");
-
-                    debug!("🎯 ROLL_STICKS 처리: 방={}, 플레이어={}", room.id, pid);
-                    debug!("🎯 방 ID 비교: 요청={}, 현재={}", rid, room.id);
 
-                    let mut inner = room.inner.write().await;
-                    if inner.status != RoomStatus::Playing {
-                        warn!("❌ 게임이 시작되지 않음: 상태={:?}", inner.status);
-                        send_err(
-                            &tx,
-                            "GAME_NOT_STARTED",
-                            "게임이 시작되지 않았습니다",
-                            json!({"roomId":room.id}),
-                        )
-                        .await;
+                    if room.id != rid {
                         continue;
                     }
 
-                    // 턴 체크
-                    let cur_pid = inner.seats.get(&inner.game.turn).map(|e| e.value().clone());
-                    debug!(
-                        "🎲 턴 체크: 현재 턴={}, 현재 플레이어={}, 요청 플레이어={}",
-                        inner.game.turn,
-                        cur_pid.as_deref().unwrap_or("없음"),
-                        pid
-                    );
-
-                    if cur_pid.as_deref() != Some(&pid) {
-                        warn!(
-                            "❌ 턴이 아님: 현재 턴 플레이어={}, 요청 플레이어={}",
-                            cur_pid.as_deref().unwrap_or("없음"),
-                            pid
-                        );
-                        send_err(
-                            &tx,
-                            "NOT_YOUR_TURN",
-                            "내 턴이 아닙니다",
-                            json!({"roomId":room.id}),
-                        )
-                        .await;
-                        continue;
+                    if let Err(e) = pass_turn(&room, pid, requested_roll).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
+                }
+            }
 
-                    let (roll, faces, _grants, can_move) = inner.game.roll();
-                    info!(
-                        "🎲 주사위 굴림 결과: roll={}, faces={:?}, can_move={}",
-                        roll, faces, can_move
-                    );
-
-                    let msg = ServerMsg::SticksRolled {
-                        room_id: room.id.clone(),
-                        game_id: inner.game_id.clone(),
-                        player_id: pid.clone(),
-                        roll,
-                        faces,
-                        turn: inner.game.turn.to_string(),
-                        can_move: can_move,
-                    };
+            // ---------- RESET_GAME ----------
+            ClientMsg::ResetGame { player_id: pid } => {
+                if let Some(room) = &joined_room {
+                    let room = room.clone();
 
-                    debug!("📤 STICKS_ROLLED 메시지 브로드캐스트: {:?}", msg);
-                    debug!(
-                        "🔍 브로드캐스트 채널 수신자 수: {}",
-                        room.tx.receiver_count()
-                    );
-
-                    match room.tx.send(msg) {
-                        Ok(receiver_count) => {
-                            info!(
-                                "✅ STICKS_ROLLED 메시지 전송 성공, 수신자 수: {}",
-                                receiver_count
-                            );
+                    match reset_game(&room, pid.clone()).await {
+                        Ok(_) => {
+                            // 게임 리셋 성공
                         }
                         Err(e) => {
-                            error!("❌ STICKS_ROLLED 메시지 전송 실패: {:?}", e);
-                            error!(
-                                "🔍 브로드캐스트 채널 상태 - 활성 수신자: {}",
-                                room.tx.receiver_count()
-                            );
+                            send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                         }
                     }
-
-                    // 이동할 수 없는 경우 자동으로 턴 패스
-                    if !can_move && !inner.game.game_over {
-                        info!(
-                            "🚫 이동 불가능 - 자동 턴 패스: 방={}, 플레이어={}",
-                            room.id, pid
-                        );
-
-                        // 턴 전환
-                        inner.game.turn = if inner.game.turn == 'W' { 'B' } else { 'W' };
-                        inner.game.last_roll = None; // 롤 값 초기화
-
-                        // 턴 변경 브로드캐스트
-                        room.tx
-                            .send(ServerMsg::TurnChanged {
-                                room_id: room.id.clone(),
-                                game_id: inner.game_id.clone(),
-                                new_turn: inner.game.turn.to_string(),
-                                reason: "no_legal_moves".to_string(),
-                            })
-                            .ok();
-                    }
-
-                    inner.last_activity = ts();
-                } else {
-                    warn!("❌ 방에 참가하지 않음");
                 }
             }
 
-            // ---------- MOVE_PIECE ----------
-            "MOVE_PIECE" => {
-                if let Some(room) = &joined_room {
-                    let room = room.clone();
-                    let rid = get_str(&data, "roomId");
+            // ---------- LEAVE_ROOM ----------
+            ClientMsg::LeaveRoom {
+                room_id: rid,
+                player_id: pid,
+            } => {
+                if let Some(room) = joined_room.take() {
                     if room.id != rid {
                         continue;
                     }
-                    let pid = get_str(&data, "playerId");
-                    let mut inner = room.inner.write().await;
-                    if inner.status != RoomStatus::Playing {
-                        send_err(
-                            &tx,
-                            "GAME_NOT_STARTED",
-                            "게임이 시작되지 않았습니다",
-                            json!({"roomId":room.id}),
-                        )
-                        .await;
+
+                    // 관전자는 관전자 목록에서만 제거
+                    if is_spectator {
+                        leave_spectator(&room, pid).await;
+                        is_spectator = false;
                         continue;
                     }
-                    let move_obj = data.get("move").cloned().unwrap_or(json!({}));
-                    let side = move_obj
-                        .get("side")
-                        .and_then(|x| x.as_str())
-                        .unwrap_or("W")
-                        .chars()
-                        .next()
-                        .unwrap();
-                    // 좌석-플레이어 검증
-                    if inner.seats.get(&side).map(|e| e.value().clone()).as_deref() != Some(&pid) {
-                        send_err(
-                            &tx,
-                            "NOT_YOUR_SIDE",
-                            "해당 진영의 플레이어가 아닙니다",
-                            json!({"roomId":room.id}),
-                        )
-                        .await;
-                        continue;
+
+                    // 방에서 플레이어 제거 (마지막 플레이어면 방 태스크가 방을 삭제)
+                    leave_room(&room, pid, None).await;
+                }
+            }
+
+            // ---------- RESUME_SESSION ----------
+            ClientMsg::ResumeSession {
+                room_id: rid,
+                player_id: pid,
+                session_token,
+            } => {
+                info!("🔁 세션 재접속 요청: 방={}, 플레이어={}", rid, pid);
+                // 이 연결이 이미 방에 있으면 거절 (먼저 LEAVE_ROOM으로 나가야 함)
+                if joined_room.is_some() {
+                    let e = if is_spectator {
+                        ServerError::AlreadySpectating
+                    } else {
+                        ServerError::AlreadyJoined
+                    };
+                    send_err(&tx, locale, e, json!({"roomId": rid})).await;
+                    continue;
+                }
+                match resume_session(&state, tx.clone(), rid.clone(), pid.clone(), session_token)
+                    .await
+                {
+                    Ok((room, _, subscription)) => {
+                        spawn_broadcast_forwarder(
+                            &room,
+                            subscription,
+                            tx.clone(),
+                            removed_tx.clone(),
+                            pid.clone(),
+                        );
+                        joined_room = Some(room);
+                        self_player_id = Some(pid);
+                        is_spectator = false;
                     }
-                    if inner.game.turn != side {
-                        send_err(
-                            &tx,
-                            "NOT_YOUR_TURN",
-                            "내 턴이 아닙니다",
-                            json!({"roomId":room.id}),
-                        )
-                        .await;
-                        continue;
+                    Err(e) => {
+                        warn!("❌ 세션 재접속 실패: {} (방: {})", e, rid);
+                        send_err(&tx, locale, e, json!({"roomId": rid})).await;
                     }
-                    let idx = move_obj
-                        .get("pieceIndex")
-                        .and_then(|x| x.as_u64())
-                        .unwrap_or(0) as usize;
-                    let from = move_obj.get("from").and_then(|x| x.as_u64()).unwrap_or(0) as u8;
-                    let to = move_obj.get("to").and_then(|x| x.as_u64()).unwrap_or(0) as u8;
-                    let roll = move_obj.get("roll").and_then(|x| x.as_u64()).unwrap_or(0) as u8;
-
-                    let (ok, extra, passed_water, captured) =
-                        inner.game.apply_move(side, idx, from, to, roll);
-                    if !ok {
+                }
+            }
+
+            // ---------- DELETE_ROOM ----------
+            ClientMsg::DeleteRoom { room_id: rid } => {
+                // 방장 확인은 그 방에 플레이어로 입장한 이 연결의 ID로 (로비 목록에 방장 ID가 보이므로)
+                let pid = match (&joined_room, &self_player_id) {
+                    (Some(room), Some(pid)) if room.id == rid && !is_spectator => pid.clone(),
+                    _ => {
                         send_err(
                             &tx,
-                            "INVALID_MOVE",
-                            "유효하지 않은 이동입니다",
-                            json!({"reason":"rule_violation"}),
+                            locale,
+                            ServerError::NotRoomOwner,
+                            json!({"roomId":rid}),
                         )
                         .await;
                         continue;
                     }
+                };
+                // 방 태스크가 남아있는 구성원들에게 삭제를 알리고 종료
+                if let Err(e) = delete_room(&state, rid.clone(), pid).await {
+                    send_err(&tx, locale, e, json!({"roomId":rid})).await;
+                }
+            }
 
-                    // 업데이트 및 브로드캐스트
-                    let move_payload = json!({"playerId": pid.clone(), "side": side.to_string(), "pieceIndex": idx, "from": from, "to": to, "roll": roll, "captured": captured.map(|(c, _)| c.to_string()), "extraTurn": extra });
-                    let gs = inner.game.snapshot();
-
-                    info!(
-                        "🔄 말 이동 브로드캐스트: 방={}, 플레이어={}, 이동={:?}",
-                        room.id, pid, move_payload
-                    );
-
-                    // 게임 상태 디버깅
-                    let game_state_json = serde_json::to_value(&gs).unwrap();
-                    debug!("📊 게임 상태: {:?}", game_state_json);
-
-                    let piece_moved_msg = ServerMsg::PieceMoved {
-                        room_id: room.id.clone(),
-                        game_id: inner.game_id.clone(),
-                        move_: move_payload.clone(),
-                        game_state: game_state_json,
-                    };
-
-                    debug!("📤 PIECE_MOVED 메시지 브로드캐스트 준비");
-                    debug!(
-                        "🔍 브로드캐스트 채널 수신자 수: {}",
-                        room.tx.receiver_count()
-                    );
-
-                    match room.tx.send(piece_moved_msg) {
-                        Ok(receiver_count) => {
-                            info!(
-                                "✅ PIECE_MOVED 메시지 전송 성공, 수신자 수: {}",
-                                receiver_count
-                            );
-                        }
-                        Err(e) => {
-                            error!("❌ PIECE_MOVED 메시지 전송 실패: {:?}", e);
-                            error!(
-                                "🔍 브로드캐스트 채널 상태 - 활성 수신자: {}",
-                                room.tx.receiver_count()
-                            );
-                        }
+            // ---------- OFFER_REMATCH / ACCEPT_REMATCH ----------
+            ClientMsg::OfferRematch {} => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = offer_rematch(&room, pid.clone()).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
-
-                    // 턴 전환
-                    let mut reason = "normal_move".to_string();
-                    if extra {
-                        reason = "extra_turn".into();
-                    } else if !extra && !passed_water {
-                        inner.game.turn = if inner.game.turn == 'W' { 'B' } else { 'W' };
-                    } else if passed_water {
-                        inner.game.turn = if inner.game.turn == 'W' { 'B' } else { 'W' };
+                }
+            }
+            ClientMsg::AcceptRematch {} => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = accept_rematch(&room, pid.clone()).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
+                }
+            }
 
-                    if !extra {
-                        room.tx
-                            .send(ServerMsg::TurnChanged {
-                                room_id: room.id.clone(),
-                                game_id: inner.game_id.clone(),
-                                new_turn: inner.game.turn.to_string(),
-                                reason,
-                            })
-                            .ok();
-                    }
-
-                    if inner.game.game_over {
-                        let winner = side.to_string();
-                        let winner_name = inner
-                            .seats
-                            .get(&side)
-                            .and_then(|e| inner.players.get(e.value()))
-                            .map(|p| p.name.clone())
-                            .unwrap_or_else(|| "Unknown".into());
-
-                        // 게임 종료 메시지 전송
-                        room.tx
-                            .send(ServerMsg::GameEnded {
-                                room_id: room.id.clone(),
-                                game_id: inner.game_id.clone(),
-                                winner,
-                                winner_name: winner_name,
-                                final_state: serde_json::to_value(gs).unwrap(),
-                                game_duration: 0,
-                            })
-                            .ok();
-
-                        // 방 상태를 Finished로 변경
-                        inner.status = RoomStatus::Finished;
-
-                        // 게임 종료 후 방 상태 변경 알림
-                        room.tx
-                            .send(ServerMsg::PlayerStatus {
-                                room_id: room.id.clone(),
-                                player_id: "system".to_string(),
-                                status: "game_finished".into(),
-                                last_seen: ts(),
-                            })
-                            .ok();
+            // ---------- CHOOSE_SEAT ----------
+            ClientMsg::ChooseSeat { side } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = choose_seat(&room, pid.clone(), side).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
-                    inner.last_activity = ts();
                 }
             }
 
-            // ---------- PASS_TURN ----------
-            "PASS_TURN" => {
-                if let Some(room) = &joined_room {
+            // ---------- SWAP_SIDES ----------
+            ClientMsg::SwapSides { accept } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
                     let room = room.clone();
-                    let pid = get_str(&data, "playerId");
-                    let rid = get_str(&data, "roomId");
-
-                    if room.id != rid {
-                        continue;
+                    if let Err(e) = swap_sides(&room, pid.clone(), accept).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
+                }
+            }
 
-                    let mut inner = room.inner.write().await;
-                    if inner.status != RoomStatus::Playing {
+            // ---------- TRANSFER_OWNERSHIP ----------
+            ClientMsg::TransferOwnership { target_id } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = transfer_ownership(&room, pid.clone(), target_id.clone()).await
+                    {
                         send_err(
                             &tx,
-                            "GAME_NOT_STARTED",
-                            "게임이 시작되지 않았습니다",
-                            json!({"roomId":room.id}),
+                            locale,
+                            e,
+                            json!({"roomId":room.id,"targetId":target_id}),
                         )
                         .await;
-                        continue;
                     }
+                }
+            }
 
-                    // 턴 체크
-                    let cur_pid = inner.seats.get(&inner.game.turn).map(|e| e.value().clone());
-                    if cur_pid.as_deref() != Some(&pid) {
+            // ---------- KICK_PLAYER / BAN_PLAYER ----------
+            ClientMsg::KickPlayer { target_id } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = kick_player(&room, pid.clone(), target_id.clone(), false).await
+                    {
                         send_err(
                             &tx,
-                            "NOT_YOUR_TURN",
-                            "내 턴이 아닙니다",
-                            json!({"roomId":room.id}),
+                            locale,
+                            e,
+                            json!({"roomId":room.id,"targetId":target_id}),
                         )
                         .await;
-                        continue;
                     }
-
-                    // 현재 롤 값 확인
-                    let current_roll = inner.game.last_roll;
-                    let requested_roll = data.get("roll").and_then(|x| x.as_u64()).map(|x| x as u8);
-
-                    if current_roll != requested_roll {
+                }
+            }
+            ClientMsg::BanPlayer { target_id } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    if let Err(e) = kick_player(&room, pid.clone(), target_id.clone(), true).await {
                         send_err(
                             &tx,
-                            "INVALID_ROLL",
-                            "잘못된 롤 값입니다",
-                            json!({"roomId":room.id}),
+                            locale,
+                            e,
+                            json!({"roomId":room.id,"targetId":target_id}),
                         )
                         .await;
-                        continue;
                     }
-
-                    // 턴 전환
-                    inner.game.turn = if inner.game.turn == 'W' { 'B' } else { 'W' };
-                    inner.game.last_roll = None; // 롤 값 초기화
-
-                    info!(
-                        "🔄 턴 패스: 방={}, 플레이어={}, 새 턴={}",
-                        room.id, pid, inner.game.turn
-                    );
-
-                    // 턴 변경 브로드캐스트
-                    room.tx
-                        .send(ServerMsg::TurnChanged {
-                            room_id: room.id.clone(),
-                            game_id: inner.game_id.clone(),
-                            new_turn: inner.game.turn.to_string(),
-                            reason: "pass_turn".to_string(),
-                        })
-                        .ok();
-
-                    inner.last_activity = ts();
                 }
             }
 
-            // ---------- RESET_GAME ----------
-            "RESET_GAME" => {
-                if let Some(room) = &joined_room {
+            // ---------- UPDATE_ROOM_SETTINGS ----------
+            ClientMsg::UpdateRoomSettings {
+                room_name,
+                password,
+                visibility,
+                variant,
+                clock,
+                start_policy,
+                best_of,
+                allow_spectators,
+                max_spectators,
+                spectator_delay,
+                players_see_spectator_chat,
+            } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
                     let room = room.clone();
-                    let pid = get_str(&data, "playerId");
+                    let update = RoomSettingsUpdate {
+                        room_name,
+                        password,
+                        visibility,
+                        variant,
+                        clock,
+                        start_policy,
+                        best_of,
+                        allow_spectators,
+                        max_spectators,
+                        spectator_delay,
+                        players_see_spectator_chat,
+                    };
+                    if let Err(e) = update_room_settings(&room, pid.clone(), update).await {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
+                    }
+                }
+            }
 
-                    match reset_game(&room, pid.clone()).await {
-                        Ok(_) => {
-                            // 게임 리셋 성공
+            // ---------- REGENERATE_INVITE_CODE ----------
+            ClientMsg::RegenerateInviteCode {} => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
+                    let room = room.clone();
+                    match regenerate_invite_code(&room, pid.clone()).await {
+                        Ok(invite_code) => {
+                            let msg = ServerMsg::InviteCodeUpdated {
+                                room_id: room.id.clone(),
+                                invite_code,
+                            };
+                            tx.send(Outgoing::new(msg)).await.ok();
                         }
                         Err(e) => {
-                            send_err(&tx, "RESET_GAME_FAILED", &e, json!({"roomId":room.id})).await;
+                            send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                         }
                     }
                 }
             }
 
-            // ---------- LEAVE_ROOM ----------
-            "LEAVE_ROOM" => {
-                if let Some(room) = joined_room.take() {
+            // ---------- CHAT_MESSAGE ----------
+            ClientMsg::ChatMessage {
+                room_id: rid,
+                message,
+                message_type,
+                channel,
+            } => {
+                if let (Some(room), Some(sender_id)) = (&joined_room, &self_player_id) {
                     let room = room.clone();
-                    let rid = get_str(&data, "roomId");
-                    let pid = get_str(&data, "playerId");
                     if room.id != rid {
                         continue;
                     }
 
-                    // 플레이어가 방을 나가기 전에 다른 플레이어들에게 알림
-                    let _player_name = self_player_name.clone().unwrap_or_else(|| "Unknown".into());
-                    room.tx
-                        .send(ServerMsg::PlayerStatus {
-                            room_id: room.id.clone(),
-                            player_id: pid.clone(),
-                            status: "left_room".into(),
-                            last_seen: ts(),
-                        })
-                        .ok();
-
-                    // 방에서 플레이어 제거
-                    let is_empty = leave_room(&room, pid.clone()).await;
-
-                    // 방이 비어있으면 즉시 삭제
-                    if is_empty {
-                        let room_id = room.id.clone();
-                        state.rooms.remove(&room_id);
-                        info!("🗑️ 빈 방 즉시 삭제: {}", room_id);
-                    }
-                }
-            }
-
-            // ---------- DELETE_ROOM ----------
-            "DELETE_ROOM" => {
-                let rid = get_str(&data, "roomId");
-                let pid = get_str(&data, "playerId");
-
-                match delete_room(&state, rid.clone(), pid).await {
-                    Ok(_) => {
-                        // 방 삭제 성공 - 방에 있던 모든 플레이어들에게 알림
-                        if let Some(room) = state.rooms.get(&rid) {
-                            room.tx
-                                .send(ServerMsg::PlayerStatus {
-                                    room_id: rid.clone(),
-                                    player_id: "system".to_string(),
-                                    status: "room_deleted".into(),
-                                    last_seen: ts(),
-                                })
-                                .ok();
-                        }
-                    }
-                    Err(e) => {
-                        send_err(&tx, "DELETE_ROOM_FAILED", &e, json!({"roomId":rid})).await;
+                    if let Err(e) =
+                        send_chat(&room, sender_id.clone(), channel, message, message_type).await
+                    {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
                 }
             }
 
-            // ---------- CHAT_MESSAGE ----------
-            "CHAT_MESSAGE" => {
-                if let Some(room) = &joined_room {
+            // ---------- SET_CHAT_POLICY ----------
+            ClientMsg::SetChatPolicy {
+                players_see_spectator_chat,
+            } => {
+                if let (Some(room), Some(pid)) = (&joined_room, &self_player_id) {
                     let room = room.clone();
-                    let rid = get_str(&data, "roomId");
-                    if room.id != rid {
-                        continue;
+                    if let Err(e) =
+                        set_chat_policy(&room, pid.clone(), players_see_spectator_chat).await
+                    {
+                        send_err(&tx, locale, e, json!({"roomId":room.id})).await;
                     }
-                    let pid = get_str(&data, "playerId");
-                    let msg = get_str(&data, "message");
-                    let name = self_player_name.clone().unwrap_or_else(|| "Player".into());
-                    let now = ts();
-                    room.tx
-                        .send(ServerMsg::ChatReceived {
-                            room_id: rid,
-                            player_id: pid,
-                            player_name: name,
-                            message: msg,
-                            message_type: data
-                                .get("messageType")
-                                .and_then(|x| x.as_str())
-                                .unwrap_or("text")
-                                .into(),
-                            timestamp: now,
-                        })
-                        .ok();
                 }
             }
 
             // ---------- HEARTBEAT ----------
-            "HEARTBEAT" => {
+            ClientMsg::Heartbeat {
+                room_id: rid,
+                player_id: pid,
+            } => {
                 if let Some(room) = &joined_room {
-                    let room = room.clone();
-                    let rid = data
-                        .get("roomId")
-                        .and_then(|x| x.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    let pid = get_str(&data, "playerId");
                     if room.id == rid {
-                        let mut inner = room.inner.write().await;
-                        inner.last_activity = ts();
-                        room.tx
-                            .send(ServerMsg::PlayerStatus {
-                                room_id: room.id.clone(),
-                                player_id: pid,
-                                status: "connected".into(),
-                                last_seen: inner.last_activity,
-                            })
-                            .ok();
+                        heartbeat(room, pid).await;
                     }
                 }
             }
-
-            _ => {
-                // 알 수 없는 메시지 타입에 대한 에러 응답
-                send_err(
-                    &tx,
-                    "UNKNOWN_MESSAGE_TYPE",
-                    &format!("알 수 없는 메시지 타입: {}", t),
-                    json!({"receivedType": t}),
-                )
-                .await;
-            }
         }
     }
 
     // 연결이 끊어졌을 때 정리 작업
     info!("🔌 클라이언트 연결 종료");
+    if let Some(task) = lobby_task {
+        task.abort();
+    }
     if let Some(room) = joined_room {
         if let Some(pid) = self_player_id {
-            info!("👋 플레이어 '{}' 연결 종료 - 방 '{}' 정리", pid, room.id);
-
-            // 다른 플레이어들에게 연결 끊김 알림
-            if let Err(e) = room.tx.send(ServerMsg::PlayerStatus {
-                room_id: room.id.clone(),
-                player_id: pid.clone(),
-                status: "disconnected".into(),
-                last_seen: ts(),
-            }) {
-                warn!("❌ 연결 끊김 알림 전송 실패: {}", e);
+            if is_spectator {
+                info!("👋 관전자 '{}' 연결 종료 - 방 '{}'", pid, room.id);
+                leave_spectator(&room, pid).await;
+                return;
             }
 
-            // 방에서 플레이어 제거 (게임 정보 초기화 포함)
-            let is_empty = leave_room(&room, pid).await;
+            info!("👋 플레이어 '{}' 연결 종료 - 방 '{}' 정리", pid, room.id);
 
-            // 방이 비어있으면 즉시 삭제
-            if is_empty {
-                let room_id = room.id.clone();
-                state.rooms.remove(&room_id);
-                info!("🗑️ 빈 방 즉시 삭제: {}", room_id);
-            }
+            // 연결 끊김 알림 후 방에서 플레이어 제거 (게임 정보 초기화 포함)
+            leave_room(&room, pid, Some(tx.clone())).await;
         }
     }
 }
 
 // ========================= 유틸리티 함수 =========================
 
-async fn send_err(tx: &mpsc::Sender<String>, code: &str, message: &str, details: Value) {
-    let env = ServerMsg::Error {
-        code: code.into(),
-        message: message.into(),
-        details,
+/// 방 태스크가 만들어 준 구독을 해당 클라이언트의 개인 채널로 전달하는 리스너를 시작합니다.
+/// 관전자의 구독은 지연 중계가 적용되는 관전자 채널입니다.
+/// 방장이 이 구성원을 내보내면 알림까지 전달하고 구독을 닫은 뒤 `removed_tx`로 방 ID를 알립니다.
+fn spawn_broadcast_forwarder(
+    room: &Arc<Room>,
+    mut brx: Subscription,
+    tx: mpsc::Sender<Arc<Outgoing>>,
+    removed_tx: mpsc::Sender<String>,
+    member_id: String,
+) {
+    let room_id = room.id.clone();
+
+    tokio::spawn(async move {
+        info!(
+            "🎯 브로드캐스트 리스너 시작 - 방: {}, 멤버: {}",
+            room_id, member_id
+        );
+        let mut msg_count = 0;
+
+        while let Ok(msg) = brx.recv().await {
+            msg_count += 1;
+            debug!(
+                "📨 브로드캐스트 메시지 수신 #{} (방: {}, 멤버: {}): {:?}",
+                msg_count, room_id, member_id, msg
+            );
+
+            let removed = msg.msg().removed_member() == Some(member_id.as_str());
+
+            // 해당 클라이언트에게 메시지 전달 (인코딩은 전송 태스크에서)
+            if let Err(e) = tx.send(msg).await {
+                error!(
+                    "❌ 클라이언트에게 메시지 전달 실패 #{} (방: {}, 멤버: {}): {:?}",
+                    msg_count, room_id, member_id, e
+                );
+                break; // 전송 실패 시 리스너 종료
+            }
+
+            if removed {
+                removed_tx.send(room_id.clone()).await.ok();
+                break;
+            }
+        }
+        info!(
+            "🔚 브로드캐스트 리스너 종료 - 방: {}, 멤버: {}, 총 메시지: {}",
+            room_id, member_id, msg_count
+        );
+    });
+}
+
+async fn send_err(
+    tx: &mpsc::Sender<Arc<Outgoing>>,
+    locale: Locale,
+    err: ServerError,
+    context: Value,
+) {
+    let _ = tx.send(Outgoing::new(err.to_msg(locale, context))).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        room::{snapshot_room, tests::play_turn},
+        types::RoomStatus,
+    };
+    use axum::{routing::get, Router};
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
+
+    /// 테스트 서버를 띄우고 WebSocket 주소를 돌려줍니다.
+    async fn serve(state: AppState) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/ws", get(ws_handler))
+            .with_state(state);
+        tokio::spawn(async move { axum::serve(listener, app).await.ok() });
+        format!("ws://{addr}/ws")
+    }
+
+    /// JSON 텍스트로 주고받는 테스트 클라이언트
+    struct Client(WebSocketStream<MaybeTlsStream<TcpStream>>);
+
+    impl Client {
+        async fn connect(url: &str) -> Self {
+            let (ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+            Client(ws)
+        }
+
+        async fn send(&mut self, msg_type: &str, data: Value) {
+            let text = json!({ "type": msg_type, "data": data }).to_string();
+            self.0.send(tungstenite::Message::Text(text)).await.unwrap();
+        }
+
+        /// 이 종류의 메시지가 올 때까지 기다려 `data`를 돌려줍니다. 그 사이 다른 메시지는 버림
+        async fn wait(&mut self, msg_type: &str) -> Value {
+            let recv = async {
+                loop {
+                    match self.0.next().await {
+                        Some(Ok(tungstenite::Message::Text(text))) => {
+                            let msg: Value = serde_json::from_str(&text).unwrap();
+                            if msg["type"] == msg_type {
+                                return msg["data"].clone();
+                            }
+                        }
+                        Some(Ok(_)) => {}
+                        other => panic!("{msg_type}을 기다리는 중 연결이 끊김: {other:?}"),
+                    }
+                }
+            };
+            tokio::time::timeout(Duration::from_secs(2), recv)
+                .await
+                .unwrap_or_else(|_| panic!("{msg_type}이 오지 않음"))
+        }
+
+        async fn error_code(&mut self) -> String {
+            self.wait("ERROR").await["code"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        }
+
+        /// 앞서 보낸 메시지가 모두 처리될 때까지 기다림 (연결의 메시지는 순서대로 처리됨)
+        async fn sync(&mut self) {
+            self.send("GET_ROOM_LIST", json!({})).await;
+            self.wait("ROOM_LIST").await;
+        }
+    }
+
+    /// alice가 방을 만들고(W) bob이 들어온(B) 상태. 서버 주소도 함께 돌려줌
+    async fn two_player_room(state: &AppState) -> (String, Client, Client, Arc<Room>) {
+        let url = serve(state.clone()).await;
+        let mut alice = Client::connect(&url).await;
+        alice
+            .send(
+                "CREATE_ROOM",
+                json!({"roomName": "r", "playerName": "Alice", "playerId": "alice", "maxPlayers": 2}),
+            )
+            .await;
+        let room_id = alice.wait("ROOM_CREATED").await["roomId"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let mut bob = Client::connect(&url).await;
+        bob.send(
+            "JOIN_ROOM",
+            json!({"roomId": room_id, "playerName": "Bob", "playerId": "bob"}),
+        )
+        .await;
+        bob.wait("ROOM_JOINED").await;
+        let room = state.rooms.get(&room_id).unwrap().clone();
+        (url, alice, bob, room)
+    }
+
+    #[tokio::test]
+    async fn swap_sides_acts_as_the_connection_player() {
+        let state = AppState::for_tests();
+        let (_, mut alice, mut bob, room) = two_player_room(&state).await;
+
+        // 상대 ID를 적어 보내도 자기 요청으로 처리되어 상대 동의 없이는 바뀌지 않음
+        alice
+            .send("SWAP_SIDES", json!({"playerId": "bob", "accept": true}))
+            .await;
+        let requested = alice.wait("SWAP_SIDES_REQUESTED").await;
+        assert_eq!(requested["requestedBy"], "alice");
+        alice
+            .send("SWAP_SIDES", json!({"playerId": "bob", "accept": true}))
+            .await;
+        alice.sync().await;
+        let snap = snapshot_room(&room).await.unwrap();
+        assert_eq!(snap.seats[&'W'], "alice");
+        assert_eq!(snap.swap_request.as_deref(), Some("alice"));
+
+        // 상대가 자기 연결에서 동의해야 바뀜
+        bob.send("SWAP_SIDES", json!({"accept": true})).await;
+        let changed = bob.wait("SEATS_CHANGED").await;
+        assert_eq!(changed["seats"], json!({"W": "bob", "B": "alice"}));
+    }
+
+    #[tokio::test]
+    async fn choose_seat_acts_as_the_connection_player() {
+        let state = AppState::for_tests();
+        let (_, mut alice, _bob, room) = two_player_room(&state).await;
+
+        // bob의 ID로 bob의 좌석을 골라도 alice가 bob의 좌석을 고른 것
+        alice
+            .send("CHOOSE_SEAT", json!({"playerId": "bob", "side": "B"}))
+            .await;
+        assert_eq!(alice.error_code().await, "SEAT_TAKEN");
+        let snap = snapshot_room(&room).await.unwrap();
+        assert_eq!(snap.seats[&'W'], "alice");
+        assert_eq!(snap.seats[&'B'], "bob");
+    }
+
+    #[tokio::test]
+    async fn rematch_cannot_be_accepted_in_the_opponents_name() {
+        let state = AppState::for_tests();
+        let (_, mut alice, mut bob, room) = two_player_room(&state).await;
+        set_ready(&room, "alice".into(), true).await;
+        set_ready(&room, "bob".into(), true).await;
+        start_game(&room, "alice".into()).await.unwrap();
+        while snapshot_room(&room).await.unwrap().status != RoomStatus::Finished {
+            play_turn(&room).await;
+        }
+
+        // 제안한 쪽이 상대 ID로 수락해도 자기 제안이라 거절
+        alice.send("OFFER_REMATCH", json!({})).await;
+        alice.wait("REMATCH_OFFERED").await;
+        alice
+            .send("ACCEPT_REMATCH", json!({"playerId": "bob"}))
+            .await;
+        assert_eq!(alice.error_code().await, "NO_REMATCH_OFFER");
+        let snap = snapshot_room(&room).await.unwrap();
+        assert_eq!(snap.status, RoomStatus::Finished);
+        let first_game = snap.game_id;
+
+        bob.send("ACCEPT_REMATCH", json!({})).await;
+        // 첫 판의 GAME_STARTED는 건너뜀
+        while bob.wait("GAME_STARTED").await["gameId"] == first_game.as_str() {}
+        assert_eq!(
+            snapshot_room(&room).await.unwrap().status,
+            RoomStatus::Playing
+        );
+    }
+
+    #[tokio::test]
+    async fn banned_player_cannot_rejoin_from_a_new_connection() {
+        let state = AppState::for_tests();
+        let (url, mut alice, bob, room) = two_player_room(&state).await;
+
+        alice.send("BAN_PLAYER", json!({"targetId": "bob"})).await;
+        alice.wait("PLAYER_STATUS").await;
+        drop(bob);
+
+        let mut bob = Client::connect(&url).await;
+        bob.send(
+            "JOIN_ROOM",
+            json!({"roomId": room.id, "playerName": "Bob", "playerId": "bob"}),
+        )
+        .await;
+        assert_eq!(bob.error_code().await, "BANNED_FROM_ROOM");
+        bob.send(
+            "SPECTATE_ROOM",
+            json!({"roomId": room.id, "playerName": "Bob", "playerId": "bob"}),
+        )
+        .await;
+        assert_eq!(bob.error_code().await, "BANNED_FROM_ROOM");
+    }
+
+    #[tokio::test]
+    async fn resume_session_is_rejected_while_in_a_room() {
+        let state = AppState::for_tests();
+        let url = serve(state.clone()).await;
+        let mut alice = Client::connect(&url).await;
+        alice
+            .send(
+                "CREATE_ROOM",
+                json!({"roomName": "a", "playerName": "Alice", "playerId": "alice", "maxPlayers": 2}),
+            )
+            .await;
+        let room_a = alice.wait("ROOM_CREATED").await["roomId"].clone();
+        let mut bob = Client::connect(&url).await;
+        bob.send(
+            "JOIN_ROOM",
+            json!({"roomId": room_a, "playerName": "Bob", "playerId": "bob"}),
+        )
+        .await;
+        let token = bob.wait("ROOM_JOINED").await["sessionToken"].clone();
+
+        let mut carol = Client::connect(&url).await;
+        carol
+            .send(
+                "CREATE_ROOM",
+                json!({"roomName": "b", "playerName": "Carol", "playerId": "carol", "maxPlayers": 2}),
+            )
+            .await;
+        let room_b = carol.wait("ROOM_CREATED").await["roomId"].clone();
+
+        // 방 B에 있는 채로 방 A의 자리로 돌아가려 하면 거절되고 방 B에 그대로 남음
+        carol
+            .send(
+                "RESUME_SESSION",
+                json!({"roomId": room_a, "playerId": "bob", "sessionToken": token}),
+            )
+            .await;
+        assert_eq!(carol.error_code().await, "ALREADY_JOINED");
+        carol
+            .send("CHAT_MESSAGE", json!({"roomId": room_b, "message": "여기"}))
+            .await;
+        assert_eq!(carol.wait("CHAT_RECEIVED").await["roomId"], room_b);
+        let room = state.rooms.get(room_a.as_str().unwrap()).unwrap().clone();
+        let snap = snapshot_room(&room).await.unwrap();
+        assert_eq!(snap.seats[&'B'], "bob");
+    }
+
+    #[tokio::test]
+    async fn delete_room_needs_the_owner_connection() {
+        let state = AppState::for_tests();
+        let (url, mut alice, mut bob, room) = two_player_room(&state).await;
+        let delete = json!({"roomId": room.id, "playerId": "alice"});
+
+        // 로비에 보이는 방장 ID를 적어 보내도 방장 연결이 아니면 거절
+        let mut outsider = Client::connect(&url).await;
+        outsider.send("DELETE_ROOM", delete.clone()).await;
+        assert_eq!(outsider.error_code().await, "NOT_ROOM_OWNER");
+        bob.send("DELETE_ROOM", delete.clone()).await;
+        assert_eq!(bob.error_code().await, "NOT_ROOM_OWNER");
+        assert!(state.rooms.contains_key(&room.id));
+
+        alice.send("DELETE_ROOM", json!({"roomId": room.id})).await;
+        alice.sync().await;
+        assert!(!state.rooms.contains_key(&room.id));
+    }
+
+    #[tokio::test]
+    async fn welcome_lists_what_the_server_provides() {
+        let url = serve(AppState::for_tests()).await;
+        let mut client = Client::connect(&url).await;
+        client
+            .send(
+                "HELLO",
+                json!({"protocolVersion": protocol::PROTOCOL_VERSION, "features": ["compression", "binary_frames"]}),
+            )
+            .await;
+        // 요청한 기능과 무관하게 이 연결에서 실제로 쓰이는 기능만
+        let welcome = client.wait("WELCOME").await;
+        assert_eq!(welcome["features"], json!(protocol::SUPPORTED_FEATURES));
     }
-    .wrap();
-    let _ = tx.send(env).await;
 }
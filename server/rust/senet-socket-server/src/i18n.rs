@@ -0,0 +1,308 @@
+use serde_json::Value;
+
+// ========================= 다국어 메시지 =========================
+
+/// 지원하는 표시 언어. 지원하지 않는 언어는 영어로 대체합니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    Ko,
+    #[default]
+    En,
+}
+
+impl Locale {
+    /// `ko`, `ko-KR`, `en-US` 같은 언어 태그 하나를 해석합니다.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "ko" => Some(Locale::Ko),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    /// `Accept-Language` 형식(`ko-KR,ko;q=0.9,en;q=0.8`)에서 가장 선호도가 높은 지원 언어를 고릅니다.
+    pub fn negotiate(accept_language: &str) -> Option<Self> {
+        let mut candidates: Vec<(f32, Locale)> = accept_language
+            .split(',')
+            .filter_map(|part| {
+                let mut it = part.split(';');
+                let locale = Locale::from_tag(it.next()?)?;
+                let q = it
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                // q=0은 "받지 않음"
+                (q > 0.0).then_some((q, locale))
+            })
+            .collect();
+        // 같은 선호도라면 먼저 나온 언어 우선 (안정 정렬)
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.first().map(|(_, l)| *l)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::Ko => "ko",
+            Locale::En => "en",
+        }
+    }
+}
+
+/// 에러 코드별 메시지. `{field}` 같은 자리표시자는 에러 details 값으로 채웁니다.
+fn error_template(locale: Locale, code: &str) -> Option<&'static str> {
+    let (ko, en) = match code {
+        "INVALID_JSON" => ("잘못된 JSON 형식입니다", "Malformed JSON message"),
+        "INVALID_MSGPACK" => (
+            "잘못된 MessagePack 형식입니다",
+            "Malformed MessagePack message",
+        ),
+        "UNKNOWN_MESSAGE_TYPE" => (
+            "알 수 없는 메시지 타입: {receivedType}",
+            "Unknown message type: {receivedType}",
+        ),
+        "INVALID_MESSAGE" => (
+            "잘못된 메시지 필드: {field}",
+            "Invalid message field: {field}",
+        ),
+        "UNSUPPORTED_PROTOCOL_VERSION" => (
+            "지원하지 않는 프로토콜 버전입니다 ({minVersion}~{maxVersion}만 가능)",
+            "Unsupported protocol version (supported: {minVersion}-{maxVersion})",
+        ),
+        "HANDSHAKE_TOO_LATE" => (
+            "HELLO는 방에 들어가기 전에 한 번만 보낼 수 있습니다",
+            "HELLO can only be sent once, before joining a room",
+        ),
+        "ROOM_NOT_FOUND" => ("방을 찾을 수 없습니다", "Room not found"),
+        "INVALID_INVITE_CODE" => (
+            "초대 코드가 올바르지 않거나 만료되었습니다",
+            "The invite code is invalid or has been revoked",
+        ),
+        "INVITE_CODE_REQUIRED" => (
+            "비공개 방은 초대 코드로만 입장할 수 있습니다",
+            "This private room can only be joined with an invite code",
+        ),
+        "NOT_IN_ROOM" => ("방에 참가하지 않았습니다", "You are not in this room"),
+        "INVALID_PASSWORD" => ("비밀번호가 올바르지 않습니다", "Incorrect room password"),
+        "TOO_MANY_PASSWORD_ATTEMPTS" => (
+            "비밀번호를 너무 많이 틀렸습니다. {retryAfterSecs}초 후에 다시 시도하세요",
+            "Too many wrong passwords. Try again in {retryAfterSecs} seconds",
+        ),
+        "INVALID_SESSION" => (
+            "세션이 만료되었거나 올바르지 않습니다",
+            "The session has expired or is invalid",
+        ),
+        "ROOM_FULL" => ("방이 가득 찼습니다", "The room is full"),
+        "SPECTATORS_FULL" => ("관전자 수가 가득 찼습니다", "No spectator slots left"),
+        "SPECTATING_DISABLED" => (
+            "이 방은 관전을 허용하지 않습니다",
+            "Spectating is disabled in this room",
+        ),
+        "ALREADY_JOINED" => (
+            "이미 방에 참가했습니다",
+            "You have already joined this room",
+        ),
+        "ALREADY_SPECTATING" => ("이미 관전 중입니다", "You are already spectating this room"),
+        "NO_AVAILABLE_SEATS" => ("빈 좌석이 없습니다", "No seat is available"),
+        "BANNED_FROM_ROOM" => (
+            "이 방에서 차단되어 입장할 수 없습니다",
+            "You have been banned from this room",
+        ),
+        "MEMBER_NOT_FOUND" => (
+            "방에 없는 플레이어입니다",
+            "That player is not in this room",
+        ),
+        "SEAT_TAKEN" => (
+            "다른 플레이어가 이미 앉은 자리입니다",
+            "Another player is already in that seat",
+        ),
+        "NOT_A_PLAYER" => (
+            "관전자가 아닌 플레이어에게만 할 수 있습니다",
+            "This can only be done to a player, not a spectator",
+        ),
+        "CANNOT_KICK_SELF" => ("자기 자신은 내보낼 수 없습니다", "You cannot kick yourself"),
+        "NOT_ROOM_OWNER" => ("방장만 할 수 있습니다", "Only the room owner can do this"),
+        "NEED_TWO_PLAYERS" => ("플레이어 두 명이 필요합니다", "Two players are required"),
+        "PLAYERS_NOT_READY" => (
+            "모든 플레이어가 준비되지 않았습니다",
+            "Not all players are ready",
+        ),
+        "GAME_NOT_STARTED" => ("게임이 시작되지 않았습니다", "The game has not started"),
+        "GAME_NOT_IN_PROGRESS" => ("진행 중인 게임이 없습니다", "No game is in progress"),
+        "GAME_IN_PROGRESS" => (
+            "게임 중에는 변경할 수 없습니다",
+            "This cannot be changed during a game",
+        ),
+        "GAME_NOT_FINISHED" => (
+            "게임이 끝난 뒤에만 할 수 있습니다",
+            "This is only possible after the game has ended",
+        ),
+        "NO_REMATCH_OFFER" => (
+            "수락할 재대결 제안이 없습니다",
+            "There is no rematch offer to accept",
+        ),
+        "NOT_YOUR_TURN" => ("내 턴이 아닙니다", "It is not your turn"),
+        "NOT_YOUR_SIDE" => (
+            "해당 진영의 플레이어가 아닙니다",
+            "You do not play that side",
+        ),
+        "INVALID_MOVE" => ("유효하지 않은 이동입니다", "That move is not allowed"),
+        "INVALID_ROLL" => ("잘못된 롤 값입니다", "The roll does not match"),
+        "SPECTATOR_NOT_ALLOWED" => (
+            "관전자는 게임을 진행할 수 없습니다",
+            "Spectators cannot take game actions",
+        ),
+        "CHANNEL_NOT_ALLOWED" => (
+            "이 채널에는 메시지를 보낼 수 없습니다",
+            "You cannot post to this chat channel",
+        ),
+        "SERVER_DRAINING" => (
+            "서버가 곧 종료되어 새 방이나 새 게임을 시작할 수 없습니다",
+            "The server is shutting down soon and is not starting new rooms or games",
+        ),
+        _ => return None,
+    };
+    Some(match locale {
+        Locale::Ko => ko,
+        Locale::En => en,
+    })
+}
+
+/// `PLAYER_STATUS` 알림(status 값)별 메시지
+fn notice_template(locale: Locale, status: &str) -> Option<&'static str> {
+    let (ko, en) = match status {
+        "connected" => ("플레이어가 접속 중입니다", "A player is connected"),
+        "disconnected" => ("플레이어의 연결이 끊어졌습니다", "A player disconnected"),
+        "left_room" => ("플레이어가 방을 나갔습니다", "A player left the room"),
+        "reconnected" => ("플레이어가 다시 접속했습니다", "A player reconnected"),
+        "session_expired" => (
+            "플레이어가 제시간에 다시 접속하지 않아 자리에서 빠졌습니다",
+            "A player did not reconnect in time and lost their seat",
+        ),
+        "kicked" => (
+            "방장이 플레이어를 내보냈습니다",
+            "A player was kicked by the owner",
+        ),
+        "banned" => (
+            "방장이 플레이어를 내보내고 차단했습니다",
+            "A player was banned by the owner",
+        ),
+        "game_cancelled" => (
+            "플레이어가 나가서 게임이 취소되었습니다",
+            "The game was cancelled because a player left",
+        ),
+        "game_finished" => ("게임이 끝났습니다", "The game is over"),
+        "room_deleted" => ("방이 삭제되었습니다", "The room was deleted"),
+        // SERVER_SHUTTING_DOWN (reason별) / SHUTDOWN_CANCELLED
+        "shutdown" => (
+            "서버가 곧 종료됩니다. 끝나지 않은 게임은 저장되어 재시작 후 이어서 할 수 있습니다",
+            "The server is shutting down soon. Unfinished games are saved and can be resumed",
+        ),
+        "maintenance" => (
+            "서버 점검이 곧 시작됩니다. 끝나지 않은 게임은 저장됩니다",
+            "Server maintenance starts soon. Unfinished games will be saved",
+        ),
+        "shutdown_cancelled" => (
+            "서버 종료가 취소되었습니다",
+            "The server shutdown was cancelled",
+        ),
+        _ => return None,
+    };
+    Some(match locale {
+        Locale::Ko => ko,
+        Locale::En => en,
+    })
+}
+
+fn fill(template: &str, params: &Value) -> String {
+    let mut out = template.to_string();
+    if let Some(map) = params.as_object() {
+        for (k, v) in map {
+            let placeholder = format!("{{{}}}", k);
+            if out.contains(&placeholder) {
+                let value = v
+                    .as_str()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| v.to_string());
+                out = out.replace(&placeholder, &value);
+            }
+        }
+    }
+    out
+}
+
+/// 에러 코드에 해당하는 메시지를 해당 언어로 만듭니다. 카탈로그에 없으면 `fallback`을 씁니다.
+pub fn error_message(locale: Locale, code: &str, details: &Value, fallback: &str) -> String {
+    match error_template(locale, code) {
+        Some(t) => fill(t, details),
+        None => fallback.to_string(),
+    }
+}
+
+/// 시스템 알림 메시지. 카탈로그에 없는 status면 `None`.
+pub fn notice_message(locale: Locale, status: &str) -> Option<String> {
+    notice_template(locale, status).map(|t| t.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ServerError;
+
+    #[test]
+    fn from_tag_reads_primary_subtag() {
+        assert_eq!(Locale::from_tag("ko"), Some(Locale::Ko));
+        assert_eq!(Locale::from_tag("ko-KR"), Some(Locale::Ko));
+        assert_eq!(Locale::from_tag(" KO_kr "), Some(Locale::Ko));
+        assert_eq!(Locale::from_tag("en-GB"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("fr-FR"), None);
+        assert_eq!(Locale::from_tag(""), None);
+    }
+
+    #[test]
+    fn negotiate_picks_highest_q() {
+        assert_eq!(
+            Locale::negotiate("ko-KR,ko;q=0.9,en;q=0.8"),
+            Some(Locale::Ko)
+        );
+        assert_eq!(Locale::negotiate("en;q=0.5, ko-KR;q=0.7"), Some(Locale::Ko));
+        assert_eq!(Locale::negotiate("ko;q=0.3,en-US"), Some(Locale::En));
+        // 같은 선호도면 먼저 나온 언어
+        assert_eq!(Locale::negotiate("en-US;q=0.8,ko;q=0.8"), Some(Locale::En));
+        // 지원하지 않는 언어는 건너뜀
+        assert_eq!(
+            Locale::negotiate("fr-FR,de;q=0.9,ko;q=0.1"),
+            Some(Locale::Ko)
+        );
+        // q=0은 받지 않는다는 뜻, 잘못된 q는 1로 봄
+        assert_eq!(Locale::negotiate("ko;q=0,en;q=0.2"), Some(Locale::En));
+        assert_eq!(Locale::negotiate("ko;q=abc,en;q=0.9"), Some(Locale::Ko));
+    }
+
+    #[test]
+    fn negotiate_without_supported_locale_falls_back() {
+        assert_eq!(Locale::negotiate(""), None);
+        assert_eq!(Locale::negotiate("fr,de;q=0.9"), None);
+        assert_eq!(Locale::negotiate("ko;q=0"), None);
+        // 호출하는 쪽은 기본값(영어)을 씀
+        assert_eq!(Locale::negotiate("fr").unwrap_or_default(), Locale::En);
+    }
+
+    #[test]
+    fn every_error_code_has_ko_and_en_messages() {
+        for e in ServerError::every_kind() {
+            let code = e.code();
+            let details = e.details();
+            let ko = error_template(Locale::Ko, code);
+            let en = error_template(Locale::En, code);
+            assert!(ko.is_some() && en.is_some(), "{code}: 카탈로그에 없음");
+            assert_ne!(ko, en, "{code}: 번역되지 않음");
+
+            for locale in [Locale::Ko, Locale::En] {
+                let msg = error_message(locale, code, &details, "");
+                assert!(!msg.is_empty(), "{code}");
+                assert!(!msg.contains('{'), "{code}: 채우지 못한 자리표시자 - {msg}");
+            }
+        }
+    }
+}
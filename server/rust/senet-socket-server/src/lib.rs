@@ -0,0 +1,19 @@
+// 서버 모듈 모음. 실행 파일(main.rs)과 벤치마크가 함께 사용합니다.
+
+pub mod admin;
+pub mod error;
+pub mod fairness;
+pub mod game;
+pub mod handlers;
+pub mod i18n;
+pub mod lobby;
+pub mod messages;
+pub mod password;
+pub mod protocol;
+pub mod rating;
+pub mod room;
+pub mod shutdown;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
+pub mod types;
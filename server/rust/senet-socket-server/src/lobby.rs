@@ -0,0 +1,672 @@
+use dashmap::{mapref::entry::Entry, DashMap};
+use rand::Rng;
+use serde_json::Value;
+use std::{collections::HashSet, sync::Arc};
+use tokio::{
+    sync::{broadcast, broadcast::error::RecvError, mpsc},
+    task::JoinHandle,
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    error::ServerError,
+    messages::ServerMsg,
+    protocol::Outgoing,
+    types::{RoomStatus, RoomSummary, RuleVariant, Visibility},
+};
+
+/// 초대 코드 길이와 글자. 읽을 때 헷갈리는 0/O, 1/I/L은 뺐습니다.
+pub const INVITE_CODE_LEN: usize = 6;
+const INVITE_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// 한 페이지 기본 방 수와 최대 방 수
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// 로비 변경 알림 채널 크기. 구독자가 이보다 많이 밀리면 목록을 처음부터 다시 보냅니다.
+const LOBBY_EVENT_QUEUE_SIZE: usize = 1024;
+
+// ========================= 로비 색인 =========================
+
+/// 방 목록용 요약 색인. 각 방 태스크가 자기 방의 요약만 올리고 지우므로
+/// 목록 조회는 방 태스크를 기다리지 않고 요약만 읽습니다.
+/// 요약이 바뀔 때마다 로비 구독자에게 변경분을 알립니다.
+pub struct Lobby {
+    rooms: DashMap<String, RoomSummary>,
+    // 초대 코드 -> 방 ID
+    invites: DashMap<String, String>,
+    events: broadcast::Sender<LobbyEvent>,
+}
+
+/// 방 요약 변경 알림. 구독자마다 필터에 따라 추가/변경/삭제 중 하나로 보이므로
+/// 세 메시지를 미리 만들어 두고, 직렬화는 실제로 보내는 것만 한 번씩 합니다.
+#[derive(Clone)]
+pub struct LobbyEvent {
+    room_id: String,
+    // None이면 방이 삭제됨
+    summary: Option<RoomSummary>,
+    added: Arc<Outgoing>,
+    updated: Arc<Outgoing>,
+    removed: Arc<Outgoing>,
+}
+
+impl LobbyEvent {
+    fn new(room_id: &str, summary: Option<RoomSummary>) -> Self {
+        let removed = Outgoing::new(ServerMsg::RoomRemoved {
+            room_id: room_id.to_string(),
+        });
+        let (added, updated) = match &summary {
+            Some(room) => (
+                Outgoing::new(ServerMsg::RoomAdded { room: room.clone() }),
+                Outgoing::new(ServerMsg::RoomUpdated {
+                    room: room.clone(),
+                    settings: None,
+                    updated_by: None,
+                }),
+            ),
+            None => (removed.clone(), removed.clone()),
+        };
+        LobbyEvent {
+            room_id: room_id.to_string(),
+            summary,
+            added,
+            updated,
+            removed,
+        }
+    }
+}
+
+impl Default for Lobby {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lobby {
+    pub fn new() -> Self {
+        let (events, _rx) = broadcast::channel(LOBBY_EVENT_QUEUE_SIZE);
+        Lobby {
+            rooms: DashMap::new(),
+            invites: DashMap::new(),
+            events,
+        }
+    }
+
+    /// 방에 겹치지 않는 새 초대 코드를 발급합니다.
+    pub fn issue_invite_code(&self, room_id: &str) -> String {
+        let mut rng = rand::thread_rng();
+        loop {
+            let code: String = (0..INVITE_CODE_LEN)
+                .map(|_| INVITE_CODE_ALPHABET[rng.gen_range(0..INVITE_CODE_ALPHABET.len())] as char)
+                .collect();
+            if let Entry::Vacant(e) = self.invites.entry(code.clone()) {
+                e.insert(room_id.to_string());
+                return code;
+            }
+        }
+    }
+
+    /// 복원한 방의 초대 코드를 다시 등록합니다. 이미 다른 방이 쓰고 있으면 `false`.
+    pub fn register_invite_code(&self, code: &str, room_id: &str) -> bool {
+        match self.invites.entry(code.to_string()) {
+            Entry::Vacant(e) => {
+                e.insert(room_id.to_string());
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    pub fn revoke_invite_code(&self, code: &str) {
+        self.invites.remove(code);
+    }
+
+    /// 초대 코드가 가리키는 방 ID
+    pub fn room_for_invite(&self, code: &str) -> Option<String> {
+        self.invites.get(code).map(|r| r.clone())
+    }
+
+    /// 방 요약을 새로 올립니다. 바뀐 것이 없으면 알리지 않고 `false`.
+    pub fn upsert(&self, summary: RoomSummary) -> bool {
+        let changed = match self.rooms.insert(summary.id.clone(), summary.clone()) {
+            Some(prev) => prev != summary,
+            None => true,
+        };
+        if changed {
+            let room_id = summary.id.clone();
+            self.events
+                .send(LobbyEvent::new(&room_id, Some(summary)))
+                .ok();
+        }
+        changed
+    }
+
+    pub fn remove(&self, room_id: &str) -> Option<RoomSummary> {
+        let removed = self.rooms.remove(room_id).map(|(_, s)| s);
+        if removed.is_some() {
+            self.events.send(LobbyEvent::new(room_id, None)).ok();
+        }
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.rooms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rooms.is_empty()
+    }
+
+    /// 상태별 방 수 (비공개 방 포함)
+    pub fn count_status(&self, status: RoomStatus) -> usize {
+        self.rooms.iter().filter(|s| s.status == status).count()
+    }
+
+    /// 필터에 맞는 방 요약 목록 (최신순)
+    pub fn list(&self, filter: &RoomFilter) -> Vec<RoomSummary> {
+        let mut rooms: Vec<RoomSummary> = self
+            .rooms
+            .iter()
+            .filter(|s| filter.matches(s.value()))
+            .map(|s| s.value().clone())
+            .collect();
+        rooms.sort_by_key(|s| SortKey::of(s, RoomSort::Newest));
+        rooms
+    }
+
+    /// 조건에 맞는 방을 정렬해서 커서 다음부터 한 페이지만 돌려줍니다.
+    pub fn page(&self, query: &RoomQuery) -> RoomPage {
+        let mut rooms: Vec<(SortKey, RoomSummary)> = self
+            .rooms
+            .iter()
+            .filter(|s| query.filter.matches(s.value()))
+            .map(|s| (SortKey::of(s.value(), query.sort), s.value().clone()))
+            .collect();
+        let total_count = rooms.len();
+        rooms.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // 커서는 이전 페이지 마지막 방의 정렬 키. 그 사이 방이 생기거나 사라져도 중복 없이 이어짐
+        let start = match &query.cursor {
+            Some(cursor) => rooms.partition_point(|(key, _)| key <= cursor),
+            None => 0,
+        };
+        let end = (start + query.limit).min(rooms.len());
+        let next_cursor = (end < rooms.len()).then(|| rooms[end - 1].0.encode());
+        let rooms = rooms.drain(start..end).map(|(_, s)| s).collect();
+
+        RoomPage {
+            rooms,
+            total_count,
+            next_cursor,
+        }
+    }
+}
+
+/// `Lobby::page` 결과
+pub struct RoomPage {
+    pub rooms: Vec<RoomSummary>,
+    pub total_count: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// 방 목록 정렬 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoomSort {
+    /// 최근에 만든 방부터
+    #[default]
+    Newest,
+    /// 관전자가 많은 방부터
+    MostSpectators,
+    /// 앉은 플레이어들의 평균 레이팅이 높은 방부터
+    HighestRated,
+}
+
+impl RoomSort {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "newest" => Some(RoomSort::Newest),
+            "spectators" => Some(RoomSort::MostSpectators),
+            "rating" => Some(RoomSort::HighestRated),
+            _ => None,
+        }
+    }
+}
+
+/// 정렬 키. 오름차순으로 비교하므로 큰 값이 먼저 오도록 뒤집어 둡니다.
+/// 마지막에 방 ID를 넣어 순서가 항상 하나로 정해지게 합니다.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey {
+    primary: u64,
+    created: u64,
+    room_id: String,
+}
+
+impl SortKey {
+    fn of(s: &RoomSummary, sort: RoomSort) -> Self {
+        let primary = match sort {
+            RoomSort::MostSpectators => u64::MAX - s.spectator_count as u64,
+            RoomSort::HighestRated => u64::MAX - s.rating as u64,
+            RoomSort::Newest => 0,
+        };
+        SortKey {
+            primary,
+            created: u64::MAX - s.created_at,
+            room_id: s.id.clone(),
+        }
+    }
+
+    /// 클라이언트에게는 그대로 돌려받기만 하는 문자열로 보냅니다.
+    fn encode(&self) -> String {
+        format!("{:x}.{:x}.{}", self.primary, self.created, self.room_id)
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let mut parts = cursor.splitn(3, '.');
+        Some(SortKey {
+            primary: u64::from_str_radix(parts.next()?, 16).ok()?,
+            created: u64::from_str_radix(parts.next()?, 16).ok()?,
+            room_id: parts.next()?.to_string(),
+        })
+    }
+}
+
+/// 필터에 정렬과 페이지 정보를 더한 목록 조회 조건
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomQuery {
+    pub filter: RoomFilter,
+    pub sort: RoomSort,
+    pub cursor: Option<SortKey>,
+    pub limit: usize,
+}
+
+impl RoomQuery {
+    /// 필터 항목에 더해 `{"sort": "newest" | "spectators" | "rating", "cursor": "...", "limit": n}`을
+    /// 해석합니다. `limit`은 1~`MAX_PAGE_SIZE`로 맞춥니다.
+    /// 필터와 달리 모르는 정렬이나 읽을 수 없는 커서는 조용히 무시하지 않고 `INVALID_MESSAGE`로 돌려줍니다
+    /// (다른 순서나 첫 페이지를 받으면 클라이언트가 알아챌 수 없음).
+    pub fn from_json(v: &Value) -> Result<Self, ServerError> {
+        let invalid = |field: &str, reason: String| ServerError::InvalidMessage {
+            msg_type: "GET_ROOM_LIST".into(),
+            field: format!("filters.{field}"),
+            reason,
+        };
+        let sort = match v.get("sort").filter(|x| !x.is_null()) {
+            None => RoomSort::default(),
+            Some(x) => x.as_str().and_then(RoomSort::from_str).ok_or_else(|| {
+                invalid(
+                    "sort",
+                    format!(
+                        "unsupported sort {x}, expected \"newest\", \"spectators\" or \"rating\""
+                    ),
+                )
+            })?,
+        };
+        let cursor = match v.get("cursor").filter(|x| !x.is_null()) {
+            None => None,
+            Some(x) => Some(
+                x.as_str()
+                    .and_then(SortKey::decode)
+                    .ok_or_else(|| invalid("cursor", format!("invalid cursor {x}")))?,
+            ),
+        };
+        Ok(RoomQuery {
+            filter: RoomFilter::from_json(v),
+            sort,
+            cursor,
+            limit: v
+                .get("limit")
+                .and_then(|x| x.as_u64())
+                .map(|n| (n as usize).clamp(1, MAX_PAGE_SIZE))
+                .unwrap_or(DEFAULT_PAGE_SIZE),
+        })
+    }
+}
+
+/// `GET_ROOM_LIST`의 `filters` 객체. 알 수 없는 값이나 `null`은 해당 조건을 적용하지 않습니다.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoomFilter {
+    pub status: Option<RoomStatus>,
+    pub has_password: Option<bool>,
+    pub max_players: Option<usize>,
+    pub variant: Option<RuleVariant>,
+    /// 빈 좌석이 이 수 이상인 방만
+    pub min_free_seats: Option<usize>,
+    /// 방 이름에 이 문자열이 들어간 방만 (대소문자 무시, 소문자로 저장)
+    pub search: Option<String>,
+}
+
+impl RoomFilter {
+    /// `{"status": "waiting" | "playing" | "finished" | "all", "hasPassword": bool,
+    /// "maxPlayers": n, "variant": "standard", "minFreeSeats": n, "search": "..."}` 형식을 해석합니다.
+    pub fn from_json(v: &Value) -> Self {
+        RoomFilter {
+            status: v
+                .get("status")
+                .and_then(|x| serde_json::from_value(x.clone()).ok()),
+            has_password: v.get("hasPassword").and_then(|x| x.as_bool()),
+            max_players: v
+                .get("maxPlayers")
+                .and_then(|x| x.as_u64())
+                .map(|n| n as usize),
+            variant: v
+                .get("variant")
+                .and_then(|x| serde_json::from_value(x.clone()).ok()),
+            min_free_seats: v
+                .get("minFreeSeats")
+                .and_then(|x| x.as_u64())
+                .map(|n| n as usize),
+            search: v
+                .get("search")
+                .and_then(|x| x.as_str())
+                .map(|q| q.trim().to_lowercase())
+                .filter(|q| !q.is_empty()),
+        }
+    }
+
+    /// 목록에는 공개 방만 보입니다.
+    pub fn matches(&self, s: &RoomSummary) -> bool {
+        s.visibility == Visibility::Public
+            && self.status.map_or(true, |st| s.status == st)
+            && self.has_password.map_or(true, |p| s.has_password == p)
+            && self.max_players.map_or(true, |m| s.max_players == m)
+            && self.variant.map_or(true, |v| s.variant == v)
+            && self.min_free_seats.map_or(true, |n| s.free_seats() >= n)
+            && self
+                .search
+                .as_ref()
+                .map_or(true, |q| s.name.to_lowercase().contains(q.as_str()))
+    }
+}
+
+// ========================= 로비 구독 =========================
+
+/// 로비 구독을 시작합니다. 먼저 필터에 맞는 `ROOM_LIST`를 보내고, 이후에는 변경분만 보냅니다.
+/// 필터에 새로 들어오는 방은 `ROOM_ADDED`, 빠지는 방은 `ROOM_REMOVED`로 보입니다.
+/// 정렬·페이지 항목은 쓰지 않고 필터에 맞는 방 전체를 최신순으로 보냅니다.
+pub fn spawn_lobby_subscription(
+    lobby: Arc<Lobby>,
+    filters: Value,
+    tx: mpsc::Sender<Arc<Outgoing>>,
+) -> JoinHandle<()> {
+    // 목록을 만들기 전에 구독해야 그 사이의 변경을 놓치지 않음
+    let mut events = lobby.events.subscribe();
+    let filter = RoomFilter::from_json(&filters);
+
+    tokio::spawn(async move {
+        info!("📋 로비 구독 시작 - 필터: {:?}", filter);
+
+        'resync: loop {
+            let rooms = lobby.list(&filter);
+            // 이 구독자에게 보이는 방
+            let mut visible: HashSet<String> = rooms.iter().map(|r| r.id.clone()).collect();
+            let list = ServerMsg::RoomList {
+                total_count: rooms.len(),
+                rooms,
+                next_cursor: None,
+                filters: filters.clone(),
+            };
+            if tx.send(Outgoing::new(list)).await.is_err() {
+                break;
+            }
+
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(n)) => {
+                        warn!("⚠️ 로비 구독 지연으로 {} 개 변경 누락 - 목록 다시 전송", n);
+                        continue 'resync;
+                    }
+                    Err(RecvError::Closed) => break 'resync,
+                };
+
+                let matches = event.summary.as_ref().is_some_and(|s| filter.matches(s));
+                let was_visible = visible.contains(&event.room_id);
+                let msg = match (was_visible, matches) {
+                    (false, true) => {
+                        visible.insert(event.room_id.clone());
+                        event.added
+                    }
+                    (true, true) => event.updated,
+                    (true, false) => {
+                        visible.remove(&event.room_id);
+                        event.removed
+                    }
+                    (false, false) => continue,
+                };
+                if tx.send(msg).await.is_err() {
+                    break 'resync;
+                }
+            }
+        }
+        debug!("🔚 로비 구독 종료");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ClockSettings;
+    use serde_json::json;
+
+    fn summary(id: &str, created_at: u64, spectators: usize) -> RoomSummary {
+        RoomSummary {
+            id: id.into(),
+            name: format!("방 {id}"),
+            status: RoomStatus::Waiting,
+            owner: "owner".into(),
+            current_players: 1,
+            max_players: 2,
+            spectator_count: spectators,
+            allow_spectators: true,
+            max_spectators: 20,
+            spectator_delay: json!({"mode": "off"}),
+            has_password: false,
+            variant: RuleVariant::Standard,
+            clock: ClockSettings::default(),
+            visibility: Visibility::Public,
+            created_at,
+            updated_at: created_at,
+            rating: 0,
+        }
+    }
+
+    fn query(v: Value) -> RoomQuery {
+        RoomQuery::from_json(&v).unwrap()
+    }
+
+    fn ids(page: &RoomPage) -> Vec<&str> {
+        page.rooms.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn sort_key_cursor_round_trip() {
+        let key = SortKey::of(
+            &summary("room.with.dots", 1_700_000_000_000, 3),
+            RoomSort::MostSpectators,
+        );
+        assert_eq!(SortKey::decode(&key.encode()), Some(key));
+    }
+
+    #[test]
+    fn pages_follow_sort_order() {
+        let lobby = Lobby::new();
+        lobby.upsert(summary("a", 1, 0));
+        lobby.upsert(summary("b", 2, 5));
+        lobby.upsert(summary("c", 3, 1));
+
+        let page = lobby.page(&query(json!({})));
+        assert_eq!(ids(&page), ["c", "b", "a"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = lobby.page(&query(json!({"sort": "spectators", "limit": 2})));
+        assert_eq!(ids(&page), ["b", "c"]);
+        assert_eq!(page.total_count, 3);
+        let cursor = page.next_cursor.unwrap();
+        let page = lobby.page(&query(json!({"sort": "spectators", "cursor": cursor})));
+        assert_eq!(ids(&page), ["a"]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn cursor_is_stable_across_inserts_and_removals() {
+        let lobby = Lobby::new();
+        for (i, id) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            lobby.upsert(summary(id, i as u64 + 1, 0));
+        }
+        let page = lobby.page(&query(json!({"limit": 2})));
+        assert_eq!(ids(&page), ["e", "d"]);
+        let cursor = page.next_cursor.unwrap();
+
+        // 첫 페이지를 받은 뒤 새 방이 생기고, 이미 본 방과 아직 안 본 방이 하나씩 사라짐
+        lobby.upsert(summary("f", 6, 0));
+        lobby.remove("e");
+        lobby.remove("c");
+
+        let page = lobby.page(&query(json!({"limit": 2, "cursor": cursor})));
+        assert_eq!(ids(&page), ["b", "a"]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn rejects_unknown_sort_and_invalid_cursor() {
+        let field = |v: Value| match RoomQuery::from_json(&v) {
+            Err(ServerError::InvalidMessage { field, .. }) => field,
+            other => panic!("INVALID_MESSAGE가 아님: {:?}", other.map(|q| q.sort)),
+        };
+        assert_eq!(field(json!({"sort": "elo"})), "filters.sort");
+        assert_eq!(field(json!({"sort": 1})), "filters.sort");
+        for cursor in [json!("garbage"), json!("zz.1.a"), json!("1.2"), json!(42)] {
+            assert_eq!(field(json!({ "cursor": cursor })), "filters.cursor");
+        }
+        // 없거나 null이면 기본값
+        assert_eq!(
+            query(json!({"sort": null, "cursor": null})).sort,
+            RoomSort::Newest
+        );
+    }
+
+    #[test]
+    fn only_public_rooms_are_listed() {
+        let lobby = Lobby::new();
+        lobby.upsert(summary("public", 1, 0));
+        for (id, visibility) in [
+            ("unlisted", Visibility::Unlisted),
+            ("private", Visibility::Private),
+        ] {
+            lobby.upsert(RoomSummary {
+                visibility,
+                ..summary(id, 2, 0)
+            });
+        }
+
+        assert_eq!(lobby.len(), 3);
+        assert_eq!(lobby.count_status(RoomStatus::Waiting), 3);
+        let listed: Vec<String> = lobby
+            .list(&RoomFilter::default())
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(listed, ["public"]);
+        let page = lobby.page(&query(json!({})));
+        assert_eq!(ids(&page), ["public"]);
+        assert_eq!(page.total_count, 1);
+    }
+
+    #[test]
+    fn invite_codes_map_to_rooms_until_revoked() {
+        let lobby = Lobby::new();
+        let code = lobby.issue_invite_code("room-1");
+        assert_eq!(code.len(), INVITE_CODE_LEN);
+        assert!(code.bytes().all(|b| INVITE_CODE_ALPHABET.contains(&b)));
+        assert_eq!(lobby.room_for_invite(&code).as_deref(), Some("room-1"));
+        assert_ne!(lobby.issue_invite_code("room-2"), code);
+
+        // 복원할 때 다른 방이 쓰는 코드는 다시 등록하지 않음
+        assert!(!lobby.register_invite_code(&code, "room-3"));
+        assert_eq!(lobby.room_for_invite(&code).as_deref(), Some("room-1"));
+
+        lobby.revoke_invite_code(&code);
+        assert_eq!(lobby.room_for_invite(&code), None);
+        assert!(lobby.register_invite_code(&code, "room-3"));
+        assert_eq!(lobby.room_for_invite(&code).as_deref(), Some("room-3"));
+    }
+
+    /// 구독이 보낸 다음 메시지 (기다려도 없으면 실패)
+    async fn next(rx: &mut mpsc::Receiver<Arc<Outgoing>>) -> Arc<Outgoing> {
+        tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .expect("구독 메시지가 오지 않음")
+            .expect("구독이 끝남")
+    }
+
+    fn listed(out: Arc<Outgoing>) -> Vec<String> {
+        match out.msg() {
+            ServerMsg::RoomList { rooms, .. } => rooms.iter().map(|r| r.id.clone()).collect(),
+            other => panic!("ROOM_LIST가 아님: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscription_sends_deltas_as_rooms_cross_the_filter() {
+        let lobby = Arc::new(Lobby::new());
+        lobby.upsert(summary("a", 1, 0));
+        let (tx, mut rx) = mpsc::channel(16);
+        spawn_lobby_subscription(lobby.clone(), json!({"status": "waiting"}), tx);
+        assert_eq!(listed(next(&mut rx).await), ["a"]);
+
+        // 필터 밖의 방은 알리지 않음
+        lobby.upsert(RoomSummary {
+            visibility: Visibility::Private,
+            ..summary("hidden", 2, 0)
+        });
+        lobby.upsert(summary("b", 3, 0));
+        assert!(
+            matches!(next(&mut rx).await.msg(), ServerMsg::RoomAdded { room } if room.id == "b")
+        );
+
+        lobby.upsert(summary("b", 3, 2));
+        assert!(
+            matches!(next(&mut rx).await.msg(), ServerMsg::RoomUpdated { room, .. } if room.id == "b")
+        );
+
+        // 상태가 바뀌어 필터에서 빠지면 삭제로 보임
+        lobby.upsert(RoomSummary {
+            status: RoomStatus::Playing,
+            ..summary("a", 1, 0)
+        });
+        assert!(
+            matches!(next(&mut rx).await.msg(), ServerMsg::RoomRemoved { room_id } if room_id == "a")
+        );
+        lobby.remove("a");
+        lobby.remove("b");
+        assert!(
+            matches!(next(&mut rx).await.msg(), ServerMsg::RoomRemoved { room_id } if room_id == "b")
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn lagged_subscription_resends_full_list() {
+        let lobby = Arc::new(Lobby::new());
+        let (tx, mut rx) = mpsc::channel(1);
+        spawn_lobby_subscription(lobby.clone(), json!({}), tx);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        // 첫 목록을 안 읽으면 구독 태스크는 다음 알림을 보내려다 멈춤
+        lobby.upsert(summary("r0", 0, 0));
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        for i in 1..=LOBBY_EVENT_QUEUE_SIZE + 10 {
+            lobby.upsert(summary(&format!("r{i}"), i as u64, 0));
+        }
+
+        assert!(listed(next(&mut rx).await).is_empty());
+        assert!(
+            matches!(next(&mut rx).await.msg(), ServerMsg::RoomAdded { room } if room.id == "r0")
+        );
+        // 밀린 변경분 대신 목록 전체를 다시 보냄
+        let rooms = listed(next(&mut rx).await);
+        assert_eq!(rooms.len(), LOBBY_EVENT_QUEUE_SIZE + 11);
+        assert_eq!(rooms[0], format!("r{}", LOBBY_EVENT_QUEUE_SIZE + 10));
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -1,17 +1,67 @@
-use axum::{routing::get, Router};
+use axum::{extract::State, http::StatusCode, routing::get, Router};
 use dashmap::DashMap;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tokio::net::TcpListener;
 use tracing::{error, info};
 
-mod game;
-mod handlers;
-mod messages;
-mod room;
-mod types;
+use senet_ws_proto::{
+    admin,
+    fairness::RollAudit,
+    handlers::ws_handler,
+    lobby::Lobby,
+    rating::{Ratings, REPLAY_LIMIT},
+    shutdown::{self, Drain, DrainReason},
+    snapshot::{self, SnapshotConfig},
+    store::{GameRepository, SqliteGameRepository},
+    types::AppState,
+};
 
-use handlers::ws_handler;
-use types::AppState;
+/// 연결을 닫은 뒤 닫기 프레임이 나갈 때까지 기다리는 시간
+const CLOSE_FLUSH: Duration = Duration::from_millis(500);
+
+/// 초 단위 환경 변수 (없거나 잘못되면 기본값)
+fn env_secs(name: &str, default: u64) -> Duration {
+    let secs = std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
+
+/// Ctrl+C 또는 SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                error!("❌ SIGTERM 핸들러 등록 실패: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// 드레인 중에는 503을 돌려줘서 로드밸런서가 새 연결을 보내지 않게 합니다.
+async fn healthz(State(state): State<AppState>) -> (StatusCode, &'static str) {
+    if state.drain.is_accepting() {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "draining")
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -23,14 +73,71 @@ async fn main() {
         .with_thread_names(true)
         .init();
 
+    let admin_token = std::env::var("SENET_ADMIN_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty())
+        .map(Arc::from);
+    if admin_token.is_none() {
+        info!("🔒 SENET_ADMIN_TOKEN이 없어 관리자 API를 사용할 수 없습니다");
+    }
+
+    // 끝난 게임 기록 (로컬 SQLite 파일)
+    let db_path = std::env::var("SENET_DB_PATH").unwrap_or_else(|_| "senet-games.db".into());
+    let games = match SqliteGameRepository::open(&db_path) {
+        Ok(repo) => {
+            info!(
+                "💾 게임 기록 저장소: {} (저장된 게임 {}개)",
+                db_path,
+                repo.count().unwrap_or(0)
+            );
+            repo
+        }
+        Err(e) => {
+            error!("❌ 게임 기록 저장소를 열 수 없습니다: {} - {}", db_path, e);
+            return;
+        }
+    };
+
+    // 레이팅은 따로 저장하지 않고 끝난 게임 기록에서 다시 계산
+    let ratings = match games.recent(None, REPLAY_LIMIT) {
+        Ok(history) => {
+            info!("📈 레이팅 계산: 게임 {}개", history.len());
+            Ratings::from_history(&history)
+        }
+        Err(e) => {
+            error!("❌ 레이팅 계산 실패, 모두 초기값으로 시작: {}", e);
+            Ratings::new()
+        }
+    };
+
+    // 재시작 전 방 상태 (정상 종료 때와 주기적으로 저장)
+    let snapshots = SnapshotConfig {
+        path: std::env::var("SENET_SNAPSHOT_PATH")
+            .unwrap_or_else(|_| "senet-rooms.json".into())
+            .into(),
+        interval: env_secs("SENET_SNAPSHOT_INTERVAL_SECS", 30),
+        resume_grace: env_secs("SENET_RESUME_GRACE_SECS", 120),
+    };
+    // 종료 신호를 받으면 진행 중인 게임을 이 시간까지 기다림
+    let shutdown_grace = env_secs("SENET_SHUTDOWN_GRACE_SECS", 60);
+
     let state = AppState {
         rooms: Arc::new(DashMap::new()),
+        lobby: Arc::new(Lobby::new()),
+        rolls: Arc::new(RollAudit::new()),
+        games: Arc::new(games),
+        ratings: Arc::new(ratings),
+        admin_token,
+        drain: Arc::new(Drain::new(shutdown_grace, snapshots.path.clone())),
     };
 
+    snapshot::restore(&state, &snapshots);
+
     let app = Router::new()
         .route("/ws", get(ws_handler))
-        .route("/healthz", get(|| async { "ok" }))
-        .with_state(state);
+        .route("/healthz", get(healthz))
+        .nest("/admin", admin::router())
+        .with_state(state.clone());
 
     let addr = "0.0.0.0:1771";
     info!("🚀 Senet WebSocket 서버 시작 중...");
@@ -40,9 +147,21 @@ async fn main() {
             info!("✅ 서버가 {} 에서 시작되었습니다", addr);
             info!("📡 WebSocket 연결을 기다리는 중...");
 
-            if let Err(e) = axum::serve(listener, app).await {
+            snapshot::spawn_periodic(state.clone(), snapshots.clone());
+
+            // 종료 신호를 받아도 드레인이 끝날 때까지는 계속 연결을 받고 게임을 진행
+            let drain_state = state.clone();
+            let serve = axum::serve(listener, app).with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                info!("🛑 종료 신호 수신 - 드레인 시작");
+                let grace = drain_state.drain.grace();
+                shutdown::drain(&drain_state, DrainReason::Shutdown, grace).await;
+            });
+            if let Err(e) = serve.await {
                 error!("❌ 서버 실행 중 오류 발생: {}", e);
             }
+            tokio::time::sleep(CLOSE_FLUSH).await;
+            info!("👋 서버 종료");
         }
         Err(e) => {
             error!("❌ 서버 바인딩 실패: {} - {}", addr, e);
@@ -1,12 +1,413 @@
-use crate::types::{ts, Envelope};
-use serde::Serialize;
+use crate::error::{ErrorCategory, ServerError};
+use crate::game::OpeningThrow;
+use crate::i18n::{self, Locale};
+use crate::password::Secret;
+use crate::stats::GameStats;
+use crate::types::{
+    ChatChannel, ClockSettings, Envelope, MatchSeries, RoomSettings, RoomStatus, RoomSummary,
+    RuleVariant, SpectatorDelay, StartPolicy, Visibility, SEATS,
+};
+use serde::{de::IntoDeserializer, Deserialize, Deserializer, Serialize};
 use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+// ========================= 클라이언트 메시지 포맷 =========================
+
+/// 클라이언트가 보내는 `{ "type": ..., "data": { ... } }` 메시지.
+/// 필수 필드가 빠지거나 타입이 맞지 않으면 기본값으로 채우지 않고 `INVALID_MESSAGE`로 거절합니다.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientMsg {
+    Hello {
+        #[serde(rename = "protocolVersion")]
+        protocol_version: u32,
+        // 클라이언트가 지원하는 기능 (로그에만 남기고 서버 동작은 바꾸지 않음)
+        #[serde(default)]
+        features: Vec<String>,
+        #[serde(default)]
+        locale: Option<String>,
+    },
+    GetRoomList {
+        #[serde(default = "empty_object")]
+        filters: Value,
+    },
+    // 로비 구독: 처음에 ROOM_LIST, 이후 ROOM_ADDED/ROOM_UPDATED/ROOM_REMOVED
+    SubscribeLobby {
+        #[serde(default = "empty_object")]
+        filters: Value,
+    },
+    UnsubscribeLobby {},
+    CreateRoom {
+        #[serde(rename = "roomName")]
+        room_name: String,
+        #[serde(default)]
+        password: Option<Secret>,
+        #[serde(rename = "maxPlayers", deserialize_with = "de_max_players")]
+        max_players: usize,
+        #[serde(rename = "allowSpectators", default)]
+        allow_spectators: Option<bool>,
+        #[serde(rename = "maxSpectators", default)]
+        max_spectators: Option<usize>,
+        #[serde(
+            rename = "spectatorDelay",
+            default,
+            deserialize_with = "de_spectator_delay"
+        )]
+        spectator_delay: Option<SpectatorDelay>,
+        #[serde(default)]
+        variant: RuleVariant,
+        #[serde(default)]
+        visibility: Visibility,
+        #[serde(rename = "playerName")]
+        player_name: String,
+        // 없으면 서버가 새로 발급
+        #[serde(rename = "playerId", default)]
+        player_id: Option<String>,
+    },
+    JoinRoom {
+        // roomId 대신 초대 코드로도 입장 가능
+        #[serde(rename = "roomId", default)]
+        room_id: Option<String>,
+        #[serde(rename = "inviteCode", default)]
+        invite_code: Option<String>,
+        #[serde(default)]
+        password: Option<Secret>,
+        #[serde(rename = "playerName")]
+        player_name: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+    },
+    SpectateRoom {
+        // roomId 대신 초대 코드로도 입장 가능
+        #[serde(rename = "roomId", default)]
+        room_id: Option<String>,
+        #[serde(rename = "inviteCode", default)]
+        invite_code: Option<String>,
+        #[serde(default)]
+        password: Option<Secret>,
+        #[serde(rename = "playerName", default)]
+        player_name: Option<String>,
+        #[serde(rename = "playerId", default)]
+        player_id: Option<String>,
+    },
+    // 방장만 가능 (요청자는 연결에 입장한 플레이어 ID로 확인)
+    SetSpectatorPolicy {
+        #[serde(rename = "allowSpectators", default)]
+        allow_spectators: Option<bool>,
+        #[serde(rename = "maxSpectators", default)]
+        max_spectators: Option<usize>,
+        #[serde(
+            rename = "spectatorDelay",
+            default,
+            deserialize_with = "de_spectator_delay"
+        )]
+        spectator_delay: Option<SpectatorDelay>,
+    },
+    ReadyStatus {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+        #[serde(rename = "isReady")]
+        is_ready: bool,
+    },
+    StartGame {
+        #[serde(rename = "playerId")]
+        player_id: String,
+    },
+    RollSticks {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+    },
+    MovePiece {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+        #[serde(rename = "move")]
+        move_: MoveRequest,
+    },
+    PassTurn {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+        // 굴리기 전에 패스하는 경우 없음
+        #[serde(default)]
+        roll: Option<u8>,
+    },
+    ResetGame {
+        #[serde(rename = "playerId")]
+        player_id: String,
+    },
+    LeaveRoom {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+    },
+    // 서버 재시작 등으로 끊긴 뒤 입장 때 받은 세션 토큰으로 자리에 돌아옴
+    ResumeSession {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+        #[serde(rename = "sessionToken")]
+        session_token: Secret,
+    },
+    // 방장만 가능 (요청자는 그 방에 입장한 연결의 플레이어 ID로 확인)
+    DeleteRoom {
+        #[serde(rename = "roomId")]
+        room_id: String,
+    },
+    // 방장이 방 설정 변경. 빠진 항목은 그대로 두고, password가 빈 문자열이면 비밀번호 해제
+    UpdateRoomSettings {
+        #[serde(rename = "roomName", default)]
+        room_name: Option<String>,
+        #[serde(default)]
+        password: Option<Secret>,
+        #[serde(default)]
+        visibility: Option<Visibility>,
+        #[serde(default)]
+        variant: Option<RuleVariant>,
+        #[serde(default)]
+        clock: Option<ClockSettings>,
+        #[serde(rename = "startPolicy", default)]
+        start_policy: Option<StartPolicy>,
+        // 홀수만 가능 (1이면 단판)
+        #[serde(rename = "bestOf", default)]
+        best_of: Option<u32>,
+        #[serde(rename = "allowSpectators", default)]
+        allow_spectators: Option<bool>,
+        #[serde(rename = "maxSpectators", default)]
+        max_spectators: Option<usize>,
+        #[serde(
+            rename = "spectatorDelay",
+            default,
+            deserialize_with = "de_spectator_delay"
+        )]
+        spectator_delay: Option<SpectatorDelay>,
+        #[serde(rename = "playersSeeSpectatorChat", default)]
+        players_see_spectator_chat: Option<bool>,
+    },
+    // 연결에 입장한 플레이어가 빈 좌석(진영)으로 옮김 (게임 중이 아닐 때)
+    ChooseSeat {
+        #[serde(deserialize_with = "de_side")]
+        side: char,
+    },
+    // 진영 교체 요청/동의 (두 플레이어가 각자 연결에서 보내야 바뀜). accept=false면 요청 취소/거절
+    SwapSides {
+        #[serde(default = "default_true")]
+        accept: bool,
+    },
+    // 게임이 끝난 뒤 같은 상대와 다시 하자고 제안 / 상대의 제안을 수락 (진영은 바꿔서 시작)
+    // 요청자는 연결에 입장한 플레이어 ID로 확인
+    OfferRematch {},
+    AcceptRematch {},
+    // 방장을 다른 플레이어에게 넘김
+    TransferOwnership {
+        #[serde(rename = "targetId")]
+        target_id: String,
+    },
+    // 방장이 플레이어나 관전자를 내보냄
+    KickPlayer {
+        #[serde(rename = "targetId")]
+        target_id: String,
+    },
+    // 내보내고 같은 ID로는 다시 입장/관전하지 못하게 차단 (재시작해도 유지)
+    BanPlayer {
+        #[serde(rename = "targetId")]
+        target_id: String,
+    },
+    // 방장이 초대 코드를 새로 발급 (예전 코드로는 더 이상 입장 불가)
+    RegenerateInviteCode {},
+    ChatMessage {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        message: String,
+        #[serde(rename = "messageType", default = "default_message_type")]
+        message_type: String,
+        #[serde(default = "default_chat_channel")]
+        channel: ChatChannel,
+    },
+    // 방장만 가능 (요청자는 연결에 입장한 플레이어 ID로 확인)
+    SetChatPolicy {
+        #[serde(rename = "playersSeeSpectatorChat", default)]
+        players_see_spectator_chat: Option<bool>,
+    },
+    Heartbeat {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveRequest {
+    #[serde(deserialize_with = "de_side")]
+    pub side: char,
+    #[serde(rename = "pieceIndex")]
+    pub piece_index: usize,
+    pub from: u8,
+    pub to: u8,
+    pub roll: u8,
+}
+
+impl ClientMsg {
+    /// JSON 텍스트 프레임을 해석합니다.
+    pub fn parse(text: &str) -> Result<Self, ServerError> {
+        let v: Value = serde_json::from_str(text).map_err(|_| ServerError::InvalidJson {
+            received: text.to_string(),
+        })?;
+        Self::from_value(v)
+    }
+
+    /// MessagePack 바이너리 프레임을 해석합니다. 스키마는 JSON과 같습니다.
+    pub fn parse_msgpack(bytes: &[u8]) -> Result<Self, ServerError> {
+        let v: Value = rmp_serde::from_slice(bytes).map_err(|_| ServerError::InvalidMsgpack {
+            received_bytes: bytes.len(),
+        })?;
+        Self::from_value(v)
+    }
+
+    fn from_value(v: Value) -> Result<Self, ServerError> {
+        let Some(msg_type) = v
+            .get("type")
+            .and_then(|x| x.as_str())
+            .map(|s| s.to_string())
+        else {
+            return Err(ServerError::InvalidMessage {
+                msg_type: String::new(),
+                field: "type".into(),
+                reason: "missing field `type`".into(),
+            });
+        };
+        let data = match v.get("data") {
+            Some(Value::Null) | None => json!({}),
+            Some(d) => d.clone(),
+        };
+
+        // type을 먼저 넘겨야 serde가 data를 버퍼링하지 않고 바로 해석해서 필드 경로를 추적할 수 있음
+        let pairs = vec![("type", Value::String(msg_type.clone())), ("data", data)];
+        let de = serde::de::value::MapDeserializer::<_, serde_json::Error>::new(
+            pairs.into_iter().map(|(k, v)| (k, v.into_deserializer())),
+        );
+        serde_path_to_error::deserialize(de).map_err(|e| {
+            let path = e.path().to_string();
+            let reason = e.into_inner().to_string();
+            if path == "type" {
+                return ServerError::UnknownMessageType {
+                    received_type: msg_type.clone(),
+                };
+            }
+            let mut field = path.strip_prefix("data.").unwrap_or(&path).to_string();
+            // missing field 오류는 상위 객체를 가리키므로 빠진 필드명을 붙여 줌
+            if let Some(name) = reason
+                .strip_prefix("missing field `")
+                .and_then(|r| r.split('`').next())
+            {
+                field = if field == "data" || field == "." || field.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", field, name)
+                };
+            }
+            ServerError::InvalidMessage {
+                msg_type: msg_type.clone(),
+                field,
+                reason,
+            }
+        })
+    }
+
+    /// 관전자가 보낼 수 없는 게임 진행 메시지인지 여부
+    pub fn is_game_action(&self) -> bool {
+        matches!(
+            self,
+            ClientMsg::ReadyStatus { .. }
+                | ClientMsg::StartGame { .. }
+                | ClientMsg::RollSticks { .. }
+                | ClientMsg::MovePiece { .. }
+                | ClientMsg::PassTurn { .. }
+                | ClientMsg::ResetGame { .. }
+                | ClientMsg::ChooseSeat { .. }
+                | ClientMsg::SwapSides { .. }
+                | ClientMsg::OfferRematch { .. }
+                | ClientMsg::AcceptRematch { .. }
+        )
+    }
+}
+
+fn empty_object() -> Value {
+    json!({})
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_message_type() -> String {
+    "text".to_string()
+}
+
+fn default_chat_channel() -> ChatChannel {
+    ChatChannel::All
+}
+
+fn de_side<'de, D: Deserializer<'de>>(d: D) -> Result<char, D::Error> {
+    match String::deserialize(d)?.as_str() {
+        "W" => Ok('W'),
+        "B" => Ok('B'),
+        other => Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(other),
+            &"\"W\" or \"B\"",
+        )),
+    }
+}
+
+fn de_max_players<'de, D: Deserializer<'de>>(d: D) -> Result<usize, D::Error> {
+    let n = u64::deserialize(d)?;
+    if n != SEATS as u64 {
+        return Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Unsigned(n),
+            &"2 (a Senet room seats two players)",
+        ));
+    }
+    Ok(SEATS)
+}
+
+fn de_spectator_delay<'de, D: Deserializer<'de>>(d: D) -> Result<Option<SpectatorDelay>, D::Error> {
+    let v = Value::deserialize(d)?;
+    SpectatorDelay::from_json(&v).map(Some).ok_or_else(|| {
+        serde::de::Error::custom(
+            "expected {\"mode\": \"off\" | \"seconds\" | \"moves\", \"value\": n}",
+        )
+    })
+}
 
 // ========================= 서버에서 쓸 메시지 포맷 =========================
 
+/// 서버가 보내는 메시지. serde가 `{ "type": "ROOM_CREATED", "data": { ... } }` 형태로 직렬화하고,
+/// 전송할 때 `Envelope`가 `timestamp`를 덧붙입니다.
 #[derive(Debug, Clone, Serialize)]
-#[serde(tag = "type", content = "data")]
+#[serde(tag = "type", content = "data", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ServerMsg {
+    Welcome {
+        #[serde(rename = "protocolVersion")]
+        protocol_version: u32,
+        #[serde(rename = "minProtocolVersion")]
+        min_protocol_version: u32,
+        #[serde(rename = "serverVersion")]
+        server_version: String,
+        // 이 연결에서 서버가 제공하는 기능 (HELLO의 features와 무관)
+        features: Vec<String>,
+        locale: String,
+        limits: Value,
+    },
     RoomCreated {
         #[serde(rename = "roomId")]
         room_id: String,
@@ -16,6 +417,46 @@ pub enum ServerMsg {
         #[serde(rename = "maxPlayers")]
         max_players: u8,
         status: String,
+        visibility: Visibility,
+        #[serde(rename = "inviteCode")]
+        invite_code: String,
+    },
+    // 좌석(진영) 변경 (reason: chosen / swapped). seats는 진영 -> 플레이어 ID
+    SeatsChanged {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        seats: BTreeMap<String, String>,
+        reason: String,
+    },
+    SwapSidesRequested {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "requestedBy")]
+        requested_by: String,
+    },
+    SwapSidesDeclined {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "declinedBy")]
+        declined_by: String,
+    },
+    // 방장 변경 (reason: transferred / owner_left)
+    OwnerChanged {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "ownerId")]
+        owner_id: String,
+        #[serde(rename = "ownerName")]
+        owner_name: String,
+        #[serde(rename = "previousOwnerId")]
+        previous_owner_id: String,
+        reason: String,
+    },
+    InviteCodeUpdated {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "inviteCode")]
+        invite_code: String,
     },
     RoomJoined {
         #[serde(rename = "roomId")]
@@ -23,8 +464,56 @@ pub enum ServerMsg {
         #[serde(rename = "roomName")]
         room_name: String,
         players: Vec<Value>,
+        spectators: Vec<Value>,
         #[serde(rename = "currentPlayer")]
         current_player: Option<Value>,
+        // 본인에게 보내는 ROOM_JOINED에만 포함 (RESUME_SESSION에 사용)
+        #[serde(rename = "sessionToken", skip_serializing_if = "Option::is_none")]
+        session_token: Option<String>,
+    },
+    // RESUME_SESSION 성공: 방과 게임의 현재 상태 (게임 중이 아니면 gameState는 null)
+    SessionResumed {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "roomName")]
+        room_name: String,
+        #[serde(rename = "playerId")]
+        player_id: String,
+        status: RoomStatus,
+        players: Vec<Value>,
+        spectators: Vec<Value>,
+        #[serde(rename = "gameId")]
+        game_id: String,
+        #[serde(rename = "gameState")]
+        game_state: Option<Value>,
+        series: MatchSeries,
+    },
+    SpectateJoined {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "roomName")]
+        room_name: String,
+        #[serde(rename = "spectatorId")]
+        spectator_id: String,
+        #[serde(rename = "spectatorName")]
+        spectator_name: String,
+        status: String,
+        players: Vec<Value>,
+        spectators: Vec<Value>,
+        #[serde(rename = "gameId")]
+        game_id: String,
+        #[serde(rename = "gameState")]
+        game_state: Option<Value>,
+    },
+    SpectatorPolicyUpdated {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "allowSpectators")]
+        allow_spectators: bool,
+        #[serde(rename = "maxSpectators")]
+        max_spectators: usize,
+        #[serde(rename = "spectatorDelay")]
+        spectator_delay: Value,
     },
     PlayerReady {
         #[serde(rename = "roomId")]
@@ -46,6 +535,12 @@ pub enum ServerMsg {
         initial_turn: String,
         #[serde(rename = "gameState")]
         game_state: Value,
+        #[serde(rename = "startPolicy")]
+        start_policy: StartPolicy,
+        // startPolicy가 openingThrow일 때 선공을 정한 굴림들
+        #[serde(rename = "openingThrows", skip_serializing_if = "Option::is_none")]
+        opening_throws: Option<Vec<OpeningThrow>>,
+        series: MatchSeries,
     },
     SticksRolled {
         #[serde(rename = "roomId")]
@@ -70,6 +565,16 @@ pub enum ServerMsg {
         #[serde(rename = "gameState")]
         game_state: Value,
     },
+    // 관전 중계가 밀려 진행 이벤트를 놓쳤을 때 보내는 현재 게임 상태 (관전 지연은 그대로 적용)
+    GameStateSync {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "gameId")]
+        game_id: String,
+        status: RoomStatus,
+        #[serde(rename = "gameState")]
+        game_state: Option<Value>,
+    },
     TurnChanged {
         #[serde(rename = "roomId")]
         room_id: String,
@@ -89,8 +594,30 @@ pub enum ServerMsg {
         winner_name: String,
         #[serde(rename = "finalState")]
         final_state: Value,
+        #[serde(rename = "startedAt")]
+        started_at: u64,
+        #[serde(rename = "endedAt")]
+        ended_at: u64,
+        // 밀리초
         #[serde(rename = "gameDuration")]
         game_duration: u64,
+        stats: GameStats,
+        series: MatchSeries,
+    },
+    // best-of-N 승부가 남 (단판이면 GAME_ENDED 바로 뒤에 옴)
+    MatchEnded {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        winner: String,
+        #[serde(rename = "winnerName")]
+        winner_name: String,
+        series: MatchSeries,
+    },
+    RematchOffered {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "offeredBy")]
+        offered_by: String,
     },
     GameReset {
         #[serde(rename = "roomId")]
@@ -109,7 +636,9 @@ pub enum ServerMsg {
         player_id: String,
         status: String,
         #[serde(rename = "lastSeen")]
-        last_seen: u128,
+        last_seen: u64,
+        // 연결별 언어로 전송 직전에 채움 (`localized`)
+        message: Option<String>,
     },
     ChatReceived {
         #[serde(rename = "roomId")]
@@ -121,227 +650,216 @@ pub enum ServerMsg {
         message: String,
         #[serde(rename = "messageType")]
         message_type: String,
-        timestamp: u128,
+        channel: String,
+        timestamp: u64,
+    },
+    ChatPolicyUpdated {
+        #[serde(rename = "roomId")]
+        room_id: String,
+        #[serde(rename = "playersSeeSpectatorChat")]
+        players_see_spectator_chat: bool,
     },
     RoomList {
-        rooms: Vec<Value>,
+        rooms: Vec<RoomSummary>,
+        // 필터에 맞는 전체 방 수 (이번 페이지 크기가 아님)
         #[serde(rename = "totalCount")]
         total_count: usize,
+        // 다음 페이지 요청에 filters.cursor로 넘기는 값. 마지막 페이지면 null
+        #[serde(rename = "nextCursor")]
+        next_cursor: Option<String>,
         filters: Value,
     },
+    // 로비 구독자에게 보내는 방 목록 변경분
+    RoomAdded {
+        room: RoomSummary,
+    },
+    // 로비 구독자에게는 room만, 방 구성원에게는 설정 변경 내용까지 보냄
+    RoomUpdated {
+        room: RoomSummary,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        settings: Option<RoomSettings>,
+        #[serde(rename = "updatedBy", skip_serializing_if = "Option::is_none")]
+        updated_by: Option<String>,
+    },
+    RoomRemoved {
+        #[serde(rename = "roomId")]
+        room_id: String,
+    },
+    // 모든 연결에 보내는 종료 예고 (카운트다운마다 다시 보냄). 마감이 지나면 소켓을 닫음
+    ServerShuttingDown {
+        reason: String,
+        deadline: u64,
+        #[serde(rename = "secondsLeft")]
+        seconds_left: u64,
+        // 연결별 언어로 전송 직전에 채움 (`localized`)
+        message: Option<String>,
+    },
+    ShutdownCancelled {
+        message: Option<String>,
+    },
     Error {
         code: String,
         message: String,
+        category: ErrorCategory,
+        status: u16,
         details: Value,
     },
 }
 
 impl ServerMsg {
-    pub fn wrap(self) -> String {
-        let (msg_type, data) = match self {
-            ServerMsg::RoomCreated {
-                room_id,
-                room_name,
-                owner,
-                max_players,
-                status,
-            } => (
-                "ROOM_CREATED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "roomName": room_name,
-                    "owner": owner,
-                    "maxPlayers": max_players,
-                    "status": status
-                }),
-            ),
-            ServerMsg::RoomJoined {
-                room_id,
-                room_name,
-                players,
-                current_player,
-            } => (
-                "ROOM_JOINED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "roomName": room_name,
-                    "players": players,
-                    "currentPlayer": current_player
-                }),
-            ),
-            ServerMsg::PlayerReady {
-                room_id,
-                player_id,
-                is_ready,
-                all_ready,
-            } => (
-                "PLAYER_READY".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "playerId": player_id,
-                    "isReady": is_ready,
-                    "allReady": all_ready
-                }),
-            ),
-            ServerMsg::GameStarted {
-                room_id,
-                game_id,
-                players,
-                initial_turn,
-                game_state,
-            } => (
-                "GAME_STARTED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "gameId": game_id,
-                    "players": players,
-                    "initialTurn": initial_turn,
-                    "gameState": game_state
-                }),
-            ),
-            ServerMsg::SticksRolled {
-                room_id,
-                game_id,
-                player_id,
-                roll,
-                faces,
-                turn,
-                can_move,
-            } => (
-                "STICKS_ROLLED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "gameId": game_id,
-                    "playerId": player_id,
-                    "roll": roll,
-                    "faces": faces,
-                    "turn": turn,
-                    "canMove": can_move
-                }),
-            ),
-            ServerMsg::PieceMoved {
-                room_id,
-                game_id,
-                move_,
-                game_state,
-            } => (
-                "PIECE_MOVED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "gameId": game_id,
-                    "move": move_,
-                    "gameState": game_state
-                }),
-            ),
-            ServerMsg::TurnChanged {
-                room_id,
-                game_id,
-                new_turn,
-                reason,
-            } => (
-                "TURN_CHANGED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "gameId": game_id,
-                    "newTurn": new_turn,
-                    "reason": reason
-                }),
-            ),
-            ServerMsg::GameEnded {
-                room_id,
-                game_id,
-                winner,
-                winner_name,
-                final_state,
-                game_duration,
-            } => (
-                "GAME_ENDED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "gameId": game_id,
-                    "winner": winner,
-                    "winnerName": winner_name,
-                    "finalState": final_state,
-                    "gameDuration": game_duration
-                }),
-            ),
-            ServerMsg::GameReset {
-                room_id,
-                game_id,
-                reset_by,
-                new_game_id,
-            } => (
-                "GAME_RESET".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "gameId": game_id,
-                    "resetBy": reset_by,
-                    "newGameId": new_game_id
-                }),
-            ),
+    /// 관전 지연 대상이 되는 게임 진행 이벤트인지 여부
+    pub fn is_game_event(&self) -> bool {
+        match self {
+            ServerMsg::SticksRolled { .. }
+            | ServerMsg::PieceMoved { .. }
+            | ServerMsg::GameStateSync { .. }
+            | ServerMsg::TurnChanged { .. }
+            | ServerMsg::GameEnded { .. }
+            | ServerMsg::MatchEnded { .. } => true,
+            // 게임이 끝났다는 알림도 지연된 마지막 수보다 먼저 나가면 결과가 드러남
+            ServerMsg::PlayerStatus { status, .. } => {
+                status == "game_finished" || status == "game_cancelled"
+            }
+            _ => false,
+        }
+    }
+
+    /// 게임을 끝내는 이벤트인지 여부 (수 단위 관전 지연은 이때 남은 수를 모두 공개)
+    pub fn ends_game(&self) -> bool {
+        match self {
+            ServerMsg::GameEnded { .. } | ServerMsg::MatchEnded { .. } => true,
+            ServerMsg::PlayerStatus { status, .. } => status == "game_cancelled",
+            _ => false,
+        }
+    }
+
+    /// 방장이 내보낸 구성원 ID (`kicked`/`banned` 알림). 이 알림을 받은 구성원은 방 구독을 닫습니다.
+    pub fn removed_member(&self) -> Option<&str> {
+        match self {
+            ServerMsg::PlayerStatus {
+                player_id, status, ..
+            } if status == "kicked" || status == "banned" => Some(player_id),
+            _ => None,
+        }
+    }
+
+    /// `locale` 언어의 시스템 알림 문구를 채운 메시지. 알림이 없는 메시지는 그대로 빌려 씁니다.
+    pub fn localized(&self, locale: Locale) -> Cow<'_, ServerMsg> {
+        match self {
             ServerMsg::PlayerStatus {
                 room_id,
                 player_id,
                 status,
                 last_seen,
-            } => (
-                "PLAYER_STATUS".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "playerId": player_id,
-                    "status": status,
-                    "lastSeen": last_seen
-                }),
-            ),
-            ServerMsg::ChatReceived {
-                room_id,
-                player_id,
-                player_name,
-                message,
-                message_type,
-                timestamp,
-            } => (
-                "CHAT_RECEIVED".to_string(),
-                json!({
-                    "roomId": room_id,
-                    "playerId": player_id,
-                    "playerName": player_name,
-                    "message": message,
-                    "messageType": message_type,
-                    "timestamp": timestamp
-                }),
-            ),
-            ServerMsg::RoomList {
-                rooms,
-                total_count,
-                filters,
-            } => (
-                "ROOM_LIST".to_string(),
-                json!({
-                    "rooms": rooms,
-                    "totalCount": total_count,
-                    "filters": filters
-                }),
-            ),
-            ServerMsg::Error {
-                code,
-                message,
-                details,
-            } => (
-                "ERROR".to_string(),
-                json!({
-                    "code": code,
-                    "message": message,
-                    "details": details
-                }),
-            ),
+                ..
+            } => Cow::Owned(ServerMsg::PlayerStatus {
+                room_id: room_id.clone(),
+                player_id: player_id.clone(),
+                status: status.clone(),
+                last_seen: *last_seen,
+                message: i18n::notice_message(locale, status),
+            }),
+            ServerMsg::ServerShuttingDown {
+                reason,
+                deadline,
+                seconds_left,
+                ..
+            } => Cow::Owned(ServerMsg::ServerShuttingDown {
+                reason: reason.clone(),
+                deadline: *deadline,
+                seconds_left: *seconds_left,
+                message: i18n::notice_message(locale, reason),
+            }),
+            ServerMsg::ShutdownCancelled { .. } => Cow::Owned(ServerMsg::ShutdownCancelled {
+                message: i18n::notice_message(locale, "shutdown_cancelled"),
+            }),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// 전송용 봉투. serde가 `{ "type", "data", "timestamp" }`로 직렬화합니다.
+    pub fn envelope(&self, locale: Locale, timestamp: u64) -> Envelope<Cow<'_, ServerMsg>> {
+        Envelope {
+            msg: self.localized(locale),
+            timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_room(data: Value) -> Result<ClientMsg, ServerError> {
+        ClientMsg::parse(&json!({"type": "CREATE_ROOM", "data": data}).to_string())
+    }
+
+    /// `INVALID_MESSAGE`의 필드 경로와 이유
+    fn invalid_field(data: Value) -> (String, String) {
+        match create_room(data) {
+            Err(ServerError::InvalidMessage {
+                msg_type,
+                field,
+                reason,
+            }) => {
+                assert_eq!(msg_type, "CREATE_ROOM");
+                (field, reason)
+            }
+            other => panic!("INVALID_MESSAGE가 아님: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn create_room_parses_required_fields() {
+        let msg = create_room(json!({"roomName": "r1", "playerName": "Ann", "maxPlayers": 2}));
+        let Ok(ClientMsg::CreateRoom {
+            room_name,
+            player_name,
+            max_players,
+            player_id,
+            ..
+        }) = msg
+        else {
+            panic!("CREATE_ROOM이 아님: {:?}", msg);
         };
+        assert_eq!(room_name, "r1");
+        assert_eq!(player_name, "Ann");
+        assert_eq!(max_players, SEATS);
+        assert_eq!(player_id, None);
+    }
 
-        serde_json::to_string(&Envelope {
-            r#type: msg_type,
-            timestamp: ts(),
-            data,
-        })
-        .unwrap()
+    #[test]
+    fn create_room_rejects_missing_fields() {
+        let full = json!({"roomName": "r1", "playerName": "Ann", "maxPlayers": 2});
+        for name in ["roomName", "playerName", "maxPlayers"] {
+            let mut data = full.clone();
+            data.as_object_mut().unwrap().remove(name);
+            let (field, reason) = invalid_field(data);
+            assert_eq!(field, name);
+            assert!(reason.starts_with("missing field"), "{}", reason);
+        }
+    }
+
+    #[test]
+    fn create_room_rejects_mistyped_fields() {
+        let (field, _) =
+            invalid_field(json!({"roomName": 1, "playerName": "Ann", "maxPlayers": 2}));
+        assert_eq!(field, "roomName");
+        let (field, _) =
+            invalid_field(json!({"roomName": "r1", "playerName": null, "maxPlayers": 2}));
+        assert_eq!(field, "playerName");
+        let (field, _) =
+            invalid_field(json!({"roomName": "r1", "playerName": "Ann", "maxPlayers": "two"}));
+        assert_eq!(field, "maxPlayers");
+    }
+
+    #[test]
+    fn create_room_rejects_out_of_range_max_players() {
+        for n in [json!(0), json!(1), json!(3), json!(300), json!(-2)] {
+            let (field, reason) =
+                invalid_field(json!({"roomName": "r1", "playerName": "Ann", "maxPlayers": n}));
+            assert_eq!(field, "maxPlayers");
+            assert!(reason.contains("invalid"), "{}", reason);
+        }
     }
 }
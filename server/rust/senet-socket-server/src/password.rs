@@ -0,0 +1,249 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand::RngCore;
+use serde::Deserialize;
+use std::{collections::VecDeque, fmt, sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+use crate::error::ServerError;
+
+// ========================= 방 비밀번호 =========================
+
+/// 한 연결에서 이 시간 동안 이만큼 틀리면 잠금
+pub const CONNECTION_MAX_FAILURES: usize = 5;
+/// 한 방에 대해 (여러 연결을 합쳐서) 이 시간 동안 이만큼 틀리면 잠금
+pub const ROOM_MAX_FAILURES: usize = 20;
+pub const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+pub const LOCKOUT: Duration = Duration::from_secs(60);
+
+/// 클라이언트가 보낸 평문 비밀번호. 로그에 남지 않도록 `Debug`는 내용을 가립니다.
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 세션 토큰처럼 평문으로 보관하는 값과 비교합니다 (상수 시간).
+    pub fn matches(&self, expected: &str) -> bool {
+        constant_time_eq(self.0.as_bytes(), expected.as_bytes())
+    }
+}
+
+/// 길이가 같으면 내용과 관계없이 같은 시간이 걸리는 비교
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+/// 방 비밀번호의 Argon2id 해시 (PHC 문자열, 솔트 포함).
+/// 해싱과 검증은 일부러 느리므로 블로킹 스레드에서 실행합니다.
+pub struct RoomPassword {
+    phc: String,
+}
+
+impl fmt::Debug for RoomPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RoomPassword(***)")
+    }
+}
+
+impl RoomPassword {
+    /// 스냅샷에 저장해 둔 PHC 문자열에서 되살립니다.
+    pub fn from_phc(phc: String) -> Arc<Self> {
+        Arc::new(RoomPassword { phc })
+    }
+
+    pub fn phc(&self) -> &str {
+        &self.phc
+    }
+
+    pub async fn hash(secret: Secret) -> Arc<Self> {
+        tokio::task::spawn_blocking(move || {
+            let mut salt = [0u8; 16];
+            rand::thread_rng().fill_bytes(&mut salt);
+            let salt = SaltString::encode_b64(&salt).expect("16바이트 솔트는 항상 인코딩 가능");
+            let phc = Argon2::default()
+                .hash_password(secret.0.as_bytes(), &salt)
+                .expect("기본 Argon2 설정으로는 해싱이 실패하지 않음")
+                .to_string();
+            Arc::new(RoomPassword { phc })
+        })
+        .await
+        .expect("비밀번호 해싱 태스크 실패")
+    }
+
+    /// 해시 비교는 argon2가 상수 시간으로 합니다.
+    pub async fn verify(self: &Arc<Self>, secret: Secret) -> bool {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let Ok(parsed) = PasswordHash::new(&this.phc) else {
+                return false;
+            };
+            Argon2::default()
+                .verify_password(secret.0.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .await
+        .unwrap_or(false)
+    }
+}
+
+// ========================= 시도 제한 =========================
+
+/// 일정 시간 안에 실패가 쌓이면 잠시 잠그는 카운터
+#[derive(Debug)]
+pub struct AttemptLimiter {
+    max_failures: usize,
+    failures: VecDeque<Instant>,
+    locked_until: Option<Instant>,
+}
+
+impl AttemptLimiter {
+    pub fn new(max_failures: usize) -> Self {
+        AttemptLimiter {
+            max_failures,
+            failures: VecDeque::new(),
+            locked_until: None,
+        }
+    }
+
+    /// 잠겨 있으면 `TOO_MANY_PASSWORD_ATTEMPTS`
+    pub fn check(&mut self) -> Result<(), ServerError> {
+        let now = Instant::now();
+        match self.locked_until {
+            Some(until) if until > now => Err(ServerError::TooManyPasswordAttempts {
+                retry_after_secs: (until - now).as_secs().max(1),
+            }),
+            Some(_) => {
+                self.locked_until = None;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_failure(&mut self) {
+        let now = Instant::now();
+        while self
+            .failures
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > FAILURE_WINDOW)
+        {
+            self.failures.pop_front();
+        }
+        self.failures.push_back(now);
+        if self.failures.len() >= self.max_failures {
+            self.failures.clear();
+            self.locked_until = Some(now + LOCKOUT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(s: &str) -> Secret {
+        Secret(s.to_string())
+    }
+
+    #[tokio::test]
+    async fn room_password_hash_and_verify() {
+        let hash = RoomPassword::hash(secret("hunter2")).await;
+        assert!(hash.phc().starts_with("$argon2id$"));
+        assert!(hash.verify(secret("hunter2")).await);
+        assert!(!hash.verify(secret("hunter3")).await);
+        assert!(!hash.verify(secret("")).await);
+
+        // 스냅샷에서 되살린 해시로도 검증됨
+        let restored = RoomPassword::from_phc(hash.phc().to_string());
+        assert!(restored.verify(secret("hunter2")).await);
+
+        // 솔트가 달라서 같은 비밀번호도 해시는 다름
+        let again = RoomPassword::hash(secret("hunter2")).await;
+        assert_ne!(again.phc(), hash.phc());
+
+        assert!(
+            !RoomPassword::from_phc("not a phc".into())
+                .verify(secret("x"))
+                .await
+        );
+    }
+
+    #[test]
+    fn constant_time_eq_compares_content_and_length() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokeN"));
+        assert!(!constant_time_eq(b"token", b"token!"));
+        assert!(!constant_time_eq(b"", b"t"));
+
+        assert!(secret("abc").matches("abc"));
+        assert!(!secret("abc").matches("abd"));
+        assert_eq!(format!("{:?}", secret("abc")), "Secret(***)");
+    }
+
+    fn retry_after(limiter: &mut AttemptLimiter) -> Option<u64> {
+        match limiter.check() {
+            Ok(()) => None,
+            Err(ServerError::TooManyPasswordAttempts { retry_after_secs }) => {
+                Some(retry_after_secs)
+            }
+            Err(e) => panic!("예상하지 못한 에러: {e}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limiter_locks_after_max_failures() {
+        for max in [CONNECTION_MAX_FAILURES, ROOM_MAX_FAILURES] {
+            let mut limiter = AttemptLimiter::new(max);
+            for _ in 0..max - 1 {
+                limiter.record_failure();
+            }
+            assert_eq!(retry_after(&mut limiter), None);
+
+            limiter.record_failure();
+            assert_eq!(retry_after(&mut limiter), Some(LOCKOUT.as_secs()));
+
+            tokio::time::advance(LOCKOUT - Duration::from_secs(1)).await;
+            assert_eq!(retry_after(&mut limiter), Some(1));
+            tokio::time::advance(Duration::from_secs(1)).await;
+            assert_eq!(retry_after(&mut limiter), None);
+
+            // 잠금이 풀리면 실패 횟수도 처음부터
+            limiter.record_failure();
+            assert_eq!(retry_after(&mut limiter), None);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limiter_forgets_failures_outside_window() {
+        for max in [CONNECTION_MAX_FAILURES, ROOM_MAX_FAILURES] {
+            let mut limiter = AttemptLimiter::new(max);
+            for _ in 0..max - 1 {
+                limiter.record_failure();
+            }
+            tokio::time::advance(FAILURE_WINDOW + Duration::from_secs(1)).await;
+            limiter.record_failure();
+            assert_eq!(retry_after(&mut limiter), None);
+
+            // 창 안의 실패만 세므로 다시 max - 1번 더 틀려야 잠김
+            for _ in 0..max - 2 {
+                limiter.record_failure();
+            }
+            assert_eq!(retry_after(&mut limiter), None);
+            limiter.record_failure();
+            assert!(retry_after(&mut limiter).is_some());
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use axum::extract::ws::Message;
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+
+use crate::{
+    error::ServerError,
+    i18n::Locale,
+    messages::ServerMsg,
+    types::{ts, DEFAULT_MAX_SPECTATORS},
+};
+
+// ========================= 프로토콜 협상 =========================
+
+/// 현재 서버가 말하는 프로토콜 버전
+pub const PROTOCOL_VERSION: u32 = 1;
+/// 아직 받아주는 가장 오래된 클라이언트 버전.
+/// `HELLO` 없이 바로 방 메시지를 보내는 예전 클라이언트는 이 버전으로 취급합니다.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// 서버가 모든 연결에 제공하는 기능. 클라이언트가 고르는 것이 아니라 `WELCOME`에 그대로 알립니다.
+/// 압축, 공정성 모드, 시간 제한은 아직 없으므로 싣지 않습니다.
+pub const SUPPORTED_FEATURES: &[&str] = &[
+    "spectators",
+    "spectator_delay",
+    "chat_channels",
+    "localization",
+];
+
+/// 바이너리(MessagePack) 프레임을 쓰는 연결에만 싣는 기능. 인코딩은 서브프로토콜로만 정합니다.
+pub const FEATURE_BINARY_FRAMES: &str = "binary_frames";
+
+/// `Sec-WebSocket-Protocol`로 고르는 인코딩. 서버 선호 순서대로 나열합니다.
+pub const SUBPROTOCOL_MSGPACK: &str = "senet.msgpack";
+pub const SUBPROTOCOL_JSON: &str = "senet.json";
+pub const SUBPROTOCOLS: [&str; 2] = [SUBPROTOCOL_MSGPACK, SUBPROTOCOL_JSON];
+
+/// 클라이언트가 보낼 수 있는 메시지 한 개의 최대 크기
+pub const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+/// 연결별 송신 대기열 크기
+pub const SEND_QUEUE_SIZE: usize = 1024;
+
+/// 연결별로 협상된 프로토콜 정보
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub protocol_version: u32,
+    /// `HELLO`를 받았는지 여부 (한 연결에서 한 번만 가능)
+    pub negotiated: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            protocol_version: MIN_PROTOCOL_VERSION,
+            negotiated: false,
+        }
+    }
+}
+
+/// 클라이언트 버전을 검사해서 세션을 만듭니다.
+pub fn negotiate(client_version: u32) -> Result<Session, ServerError> {
+    if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&client_version) {
+        return Err(ServerError::UnsupportedProtocolVersion { client_version });
+    }
+    Ok(Session {
+        protocol_version: client_version,
+        negotiated: true,
+    })
+}
+
+/// `WELCOME`에 싣는 기능 목록: 이 연결에서 실제로 쓰이는 것만
+pub fn server_features(format: WireFormat) -> Vec<String> {
+    let mut features: Vec<String> = SUPPORTED_FEATURES.iter().map(|f| f.to_string()).collect();
+    if format == WireFormat::MessagePack {
+        features.push(FEATURE_BINARY_FRAMES.to_string());
+    }
+    features
+}
+
+/// `WELCOME`에 실어 보내는 서버 제한값
+pub fn server_limits() -> Value {
+    json!({
+        "maxMessageBytes": MAX_MESSAGE_BYTES,
+        "sendQueueSize": SEND_QUEUE_SIZE,
+        "maxPlayers": 2,
+        "defaultMaxSpectators": DEFAULT_MAX_SPECTATORS
+    })
+}
+
+// ========================= 전송 인코딩 =========================
+
+/// 서버가 보내는 프레임의 인코딩. 서브프로토콜을 고르지 않은 클라이언트는 JSON을 받습니다.
+/// 수신은 인코딩과 상관없이 텍스트 프레임은 JSON, 바이너리 프레임은 MessagePack으로 해석합니다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl WireFormat {
+    pub fn from_subprotocol(protocol: Option<&str>) -> Self {
+        match protocol {
+            Some(SUBPROTOCOL_MSGPACK) => WireFormat::MessagePack,
+            _ => WireFormat::Json,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WireFormat::Json => "json",
+            WireFormat::MessagePack => "msgpack",
+        }
+    }
+
+    /// 메시지를 이 인코딩으로 직렬화합니다. MessagePack은 JSON과 같은 스키마를
+    /// 필드 이름이 있는 map 형식으로 씁니다.
+    pub fn encode(&self, msg: &ServerMsg, locale: Locale, timestamp: u64) -> Frame {
+        let envelope = msg.envelope(locale, timestamp);
+        match self {
+            WireFormat::Json => Frame::Text(serde_json::to_string(&envelope).unwrap().into()),
+            WireFormat::MessagePack => {
+                Frame::Binary(rmp_serde::to_vec_named(&envelope).unwrap().into())
+            }
+        }
+    }
+}
+
+/// 직렬화가 끝난 프레임. 복제해도 버퍼는 공유됩니다.
+#[derive(Debug, Clone)]
+pub enum Frame {
+    Text(Arc<str>),
+    Binary(Bytes),
+}
+
+impl Frame {
+    pub fn byte_len(&self) -> usize {
+        match self {
+            Frame::Text(t) => t.len(),
+            Frame::Binary(b) => b.len(),
+        }
+    }
+
+    /// axum 0.7의 `Message`는 버퍼를 소유해야 하므로 소켓에 쓰기 직전에 한 번 복사합니다.
+    pub fn to_message(&self) -> Message {
+        match self {
+            Frame::Text(t) => Message::Text(t.to_string()),
+            Frame::Binary(b) => Message::Binary(b.to_vec()),
+        }
+    }
+}
+
+/// 전송 대기열에 넣는 메시지. (인코딩, 언어) 조합별 프레임을 처음 필요할 때 한 번만 만들어 둡니다.
+/// 방 브로드캐스트는 이것을 `Arc`로 나눠 가지므로 구독자가 몇 명이든 조합당 한 번만 직렬화합니다.
+#[derive(Debug)]
+pub struct Outgoing {
+    msg: ServerMsg,
+    timestamp: u64,
+    frames: [OnceLock<Frame>; 4],
+}
+
+impl Outgoing {
+    pub fn new(msg: ServerMsg) -> Arc<Self> {
+        Arc::new(Outgoing {
+            msg,
+            timestamp: ts(),
+            frames: Default::default(),
+        })
+    }
+
+    pub fn msg(&self) -> &ServerMsg {
+        &self.msg
+    }
+
+    pub fn frame(&self, format: WireFormat, locale: Locale) -> Frame {
+        let slot = match (format, locale) {
+            (WireFormat::Json, Locale::En) => 0,
+            (WireFormat::Json, Locale::Ko) => 1,
+            (WireFormat::MessagePack, Locale::En) => 2,
+            (WireFormat::MessagePack, Locale::Ko) => 3,
+        };
+        self.frames[slot]
+            .get_or_init(|| format.encode(&self.msg, locale, self.timestamp))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::ErrorCategory,
+        messages::ClientMsg,
+        stats::GameStats,
+        types::{MatchSeries, Visibility},
+    };
+
+    fn samples() -> Vec<ServerMsg> {
+        vec![
+            ServerMsg::RoomCreated {
+                room_id: "r1".into(),
+                room_name: "세넷 방".into(),
+                owner: "p1".into(),
+                max_players: 2,
+                status: "waiting".into(),
+                visibility: Visibility::Private,
+                invite_code: "K7PQ2X".into(),
+            },
+            ServerMsg::SticksRolled {
+                room_id: "r1".into(),
+                game_id: "g1".into(),
+                player_id: "p1".into(),
+                roll: 5,
+                faces: [0, 0, 0, 0],
+                turn: "W".into(),
+                can_move: true,
+            },
+            ServerMsg::PlayerStatus {
+                room_id: "r1".into(),
+                player_id: "p2".into(),
+                status: "disconnected".into(),
+                last_seen: 1_700_000_000_000,
+                message: None,
+            },
+            ServerMsg::GameEnded {
+                room_id: "r1".into(),
+                game_id: "g1".into(),
+                winner: "B".into(),
+                winner_name: "Bob".into(),
+                final_state: json!({"pieces": {"W": [1, 2, null], "B": []}, "turn": "B"}),
+                started_at: 1_700_000_000_000,
+                ended_at: 1_700_000_600_000,
+                game_duration: 600_000,
+                stats: GameStats::new('W'),
+                series: MatchSeries {
+                    best_of: 3,
+                    game_number: 2,
+                    wins: [("p1".to_string(), 1), ("p2".to_string(), 1)].into(),
+                },
+            },
+            ServerMsg::Error {
+                code: "ROOM_FULL".into(),
+                message: "The room is full".into(),
+                category: ErrorCategory::Conflict,
+                status: 409,
+                details: json!({"roomId": "r1"}),
+            },
+        ]
+    }
+
+    fn decode(frame: Frame) -> Value {
+        match frame {
+            Frame::Text(t) => serde_json::from_str(&t).unwrap(),
+            Frame::Binary(b) => rmp_serde::from_slice(&b).unwrap(),
+        }
+    }
+
+    const TS: u64 = 1_700_000_000_123;
+
+    #[test]
+    fn json_frames_are_text_and_round_trip() {
+        for msg in samples() {
+            let expected = serde_json::to_value(msg.envelope(Locale::En, TS)).unwrap();
+            let frame = WireFormat::Json.encode(&msg, Locale::En, TS);
+            assert!(matches!(frame, Frame::Text(_)));
+            assert!(matches!(frame.to_message(), Message::Text(_)));
+            assert_eq!(decode(frame), expected);
+        }
+    }
+
+    #[test]
+    fn msgpack_frames_are_binary_and_round_trip() {
+        for msg in samples() {
+            let expected = serde_json::to_value(msg.envelope(Locale::En, TS)).unwrap();
+            let frame = WireFormat::MessagePack.encode(&msg, Locale::En, TS);
+            assert!(matches!(frame, Frame::Binary(_)));
+            assert!(matches!(frame.to_message(), Message::Binary(_)));
+            assert_eq!(decode(frame), expected);
+        }
+    }
+
+    #[test]
+    fn both_encodings_carry_the_same_message() {
+        for msg in samples() {
+            let json = decode(WireFormat::Json.encode(&msg, Locale::Ko, TS));
+            let msgpack = decode(WireFormat::MessagePack.encode(&msg, Locale::Ko, TS));
+            assert_eq!(json, msgpack);
+        }
+    }
+
+    #[test]
+    fn envelope_keeps_the_wire_schema() {
+        let msg = &samples()[0];
+        assert_eq!(
+            decode(WireFormat::Json.encode(msg, Locale::En, TS)),
+            json!({
+                "type": "ROOM_CREATED",
+                "timestamp": TS,
+                "data": {
+                    "roomId": "r1",
+                    "roomName": "세넷 방",
+                    "owner": "p1",
+                    "maxPlayers": 2,
+                    "status": "waiting",
+                    "visibility": "private",
+                    "inviteCode": "K7PQ2X"
+                }
+            })
+        );
+
+        // 시스템 알림 문구는 연결의 언어로 채움
+        let status = &samples()[2];
+        let ko = decode(WireFormat::MessagePack.encode(status, Locale::Ko, TS));
+        let en = decode(WireFormat::Json.encode(status, Locale::En, TS));
+        assert_eq!(ko["data"]["message"], "플레이어의 연결이 끊어졌습니다");
+        assert_eq!(en["data"]["message"], "A player disconnected");
+    }
+
+    #[test]
+    fn outgoing_serializes_once_per_encoding() {
+        let out = Outgoing::new(samples()[1].clone());
+        let (Frame::Text(a), Frame::Text(b)) = (
+            out.frame(WireFormat::Json, Locale::En),
+            out.frame(WireFormat::Json, Locale::En),
+        ) else {
+            panic!("expected text frames");
+        };
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(matches!(
+            out.frame(WireFormat::MessagePack, Locale::En),
+            Frame::Binary(_)
+        ));
+    }
+
+    #[test]
+    fn client_messages_parse_from_either_encoding() {
+        let v = json!({
+            "type": "JOIN_ROOM",
+            "data": {"roomId": "r1", "playerName": "Ann", "playerId": "p1"}
+        });
+        let from_json = ClientMsg::parse(&v.to_string()).unwrap();
+        let from_msgpack = ClientMsg::parse_msgpack(&rmp_serde::to_vec_named(&v).unwrap()).unwrap();
+        assert_eq!(format!("{:?}", from_json), format!("{:?}", from_msgpack));
+
+        let err = ClientMsg::parse_msgpack(&[0xc1]).unwrap_err();
+        assert_eq!(err.code(), "INVALID_MSGPACK");
+    }
+
+    #[test]
+    fn subprotocol_selects_encoding() {
+        assert_eq!(
+            WireFormat::from_subprotocol(Some("senet.msgpack")),
+            WireFormat::MessagePack
+        );
+        assert_eq!(
+            WireFormat::from_subprotocol(Some("senet.json")),
+            WireFormat::Json
+        );
+        assert_eq!(WireFormat::from_subprotocol(None), WireFormat::Json);
+    }
+
+    #[test]
+    fn binary_frames_follow_the_wire_format() {
+        assert_eq!(server_features(WireFormat::Json), SUPPORTED_FEATURES);
+        let msgpack = server_features(WireFormat::MessagePack);
+        assert_eq!(
+            msgpack.last().map(String::as_str),
+            Some(FEATURE_BINARY_FRAMES)
+        );
+        assert_eq!(msgpack.len(), SUPPORTED_FEATURES.len() + 1);
+    }
+
+    #[test]
+    fn negotiate_checks_the_version_range() {
+        assert_eq!(
+            negotiate(PROTOCOL_VERSION).unwrap().protocol_version,
+            PROTOCOL_VERSION
+        );
+        for client_version in [MIN_PROTOCOL_VERSION - 1, PROTOCOL_VERSION + 1] {
+            assert!(matches!(
+                negotiate(client_version),
+                Err(ServerError::UnsupportedProtocolVersion { .. })
+            ));
+        }
+    }
+}
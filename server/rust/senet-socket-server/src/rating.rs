@@ -0,0 +1,120 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::stats::GameRecord;
+
+// ========================= 플레이어 레이팅 =========================
+
+/// 처음 보는 플레이어의 레이팅
+pub const INITIAL_RATING: f64 = 1200.0;
+/// 한 판에 오갈 수 있는 최대 점수 (Elo K 계수)
+const K_FACTOR: f64 = 32.0;
+/// 시작할 때 다시 계산하는 최근 게임 수
+pub const REPLAY_LIMIT: usize = 100_000;
+
+/// 플레이어 ID별 Elo 레이팅. 게임이 끝날 때마다 두 플레이어의 점수를 옮기고,
+/// 재시작하면 저장된 게임 기록을 끝난 순서대로 다시 적용해 복원합니다.
+#[derive(Default)]
+pub struct Ratings {
+    // 두 플레이어를 한 번에 고쳐야 하므로 맵 전체를 잠금
+    ratings: Mutex<HashMap<String, f64>>,
+}
+
+impl Ratings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 저장소의 `recent` 결과(최근 순)에서 레이팅을 다시 계산합니다.
+    pub fn from_history(recent_first: &[GameRecord]) -> Self {
+        let ratings = Ratings::new();
+        for game in recent_first.iter().rev() {
+            ratings.record(game);
+        }
+        ratings
+    }
+
+    /// 플레이어 레이팅 (반올림). 기록이 없으면 `INITIAL_RATING`.
+    pub fn get(&self, player_id: &str) -> u32 {
+        let ratings = self.ratings.lock().unwrap();
+        ratings
+            .get(player_id)
+            .copied()
+            .unwrap_or(INITIAL_RATING)
+            .round() as u32
+    }
+
+    /// 끝난 게임 결과를 반영합니다. 상대가 없거나 혼자 둔 게임은 건너뜁니다.
+    pub fn record(&self, game: &GameRecord) {
+        let Some(loser) = game.players.iter().find(|p| p.player_id != game.winner_id) else {
+            return;
+        };
+        let mut ratings = self.ratings.lock().unwrap();
+        let winner_rating = *ratings.get(&game.winner_id).unwrap_or(&INITIAL_RATING);
+        let loser_rating = *ratings.get(&loser.player_id).unwrap_or(&INITIAL_RATING);
+        let expected = 1.0 / (1.0 + 10f64.powf((loser_rating - winner_rating) / 400.0));
+        let delta = K_FACTOR * (1.0 - expected);
+        ratings.insert(game.winner_id.clone(), winner_rating + delta);
+        ratings.insert(loser.player_id.clone(), loser_rating - delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stats::{GameStats, RecordPlayer},
+        types::{MatchSeries, RuleVariant},
+    };
+
+    fn game(winner: &str, loser: &str) -> GameRecord {
+        GameRecord {
+            game_id: format!("{winner}-{loser}"),
+            room_id: "room".into(),
+            variant: RuleVariant::Standard,
+            seed: 0,
+            players: [('W', winner), ('B', loser)]
+                .into_iter()
+                .map(|(side, id)| RecordPlayer {
+                    side,
+                    player_id: id.into(),
+                    player_name: id.into(),
+                })
+                .collect(),
+            winner: 'W',
+            winner_id: winner.into(),
+            started_at: 0,
+            ended_at: 0,
+            duration_ms: 0,
+            stats: GameStats::new('W'),
+            series: MatchSeries::new(1),
+            moves: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn winner_takes_points_from_loser() {
+        let ratings = Ratings::new();
+        assert_eq!(ratings.get("alice"), 1200);
+
+        // 같은 점수끼리는 K/2씩 오감
+        ratings.record(&game("alice", "bob"));
+        assert_eq!((ratings.get("alice"), ratings.get("bob")), (1216, 1184));
+
+        // 약한 쪽이 이기면 더 많이 가져감
+        ratings.record(&game("bob", "alice"));
+        assert_eq!((ratings.get("alice"), ratings.get("bob")), (1199, 1201));
+    }
+
+    #[test]
+    fn history_is_replayed_oldest_first() {
+        let recent_first = [game("bob", "alice"), game("alice", "bob")];
+        let replayed = Ratings::from_history(&recent_first);
+
+        let live = Ratings::new();
+        live.record(&recent_first[1]);
+        live.record(&recent_first[0]);
+        for id in ["alice", "bob"] {
+            assert_eq!(replayed.get(id), live.get(id));
+        }
+    }
+}
@@ -1,67 +1,306 @@
 use dashmap::DashMap;
 use serde_json::json;
-use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{broadcast, broadcast::error::RecvError, mpsc, oneshot, watch},
+    time::Instant,
+};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    game::GameState,
-    messages::ServerMsg,
-    types::{ts, AppState, Player, Room, RoomInner, RoomStatus},
+    error::ServerError,
+    fairness::RollAudit,
+    game::{self, GameState, OpeningThrow, WATER_SQUARE},
+    lobby::{Lobby, RoomQuery},
+    messages::{MoveRequest, ServerMsg},
+    password::{AttemptLimiter, RoomPassword, Secret, ROOM_MAX_FAILURES},
+    protocol::Outgoing,
+    rating::Ratings,
+    shutdown::Drain,
+    snapshot::{PlayerSnapshot, RoomSnapshot},
+    stats::{GameRecord, GameStats, MoveRecord, RecordPlayer},
+    store::{self, GameRepository},
+    types::{
+        ts, AppState, ChatChannel, ChatPolicy, ClockSettings, MatchSeries, Player, Room, RoomInner,
+        RoomRef, RoomSettings, RoomStatus, RoomSummary, RuleVariant, Spectator, SpectatorDelay,
+        SpectatorPolicy, StartPolicy, Visibility,
+    },
 };
 
+/// 방 명령 큐 크기
+const ROOM_QUEUE_SIZE: usize = 256;
+
+type Reply<T> = oneshot::Sender<T>;
+type MemberTx = mpsc::Sender<Arc<Outgoing>>;
+/// 방 브로드캐스트 구독. 방 태스크가 입장 처리 직후에 만들어 주므로 입장 뒤의 이벤트를 빠짐없이 받습니다.
+pub type Subscription = broadcast::Receiver<Arc<Outgoing>>;
+
+// ========================= 방 명령 =========================
+
+/// 방 태스크가 처리하는 명령. 방 상태 변경은 모두 이 큐를 거치므로 한 방의 이벤트 순서는 항상 같습니다.
+pub enum RoomCmd {
+    Join {
+        tx: MemberTx,
+        // 초대 코드로 찾아온 경우 (비공개 방은 이때만 입장 가능)
+        invited: bool,
+        // 방 태스크 밖에서 검증을 통과한 비밀번호 해시
+        verified: Option<Arc<RoomPassword>>,
+        player_name: String,
+        player_id: String,
+        reply: Reply<Result<Subscription, ServerError>>,
+    },
+    /// 입장 전 비밀번호 확인용으로 현재 해시를 받아감 (방이 잠겨 있으면 에러)
+    PasswordGate {
+        reply: Reply<Result<Option<Arc<RoomPassword>>, ServerError>>,
+    },
+    PasswordFailed,
+    Leave {
+        player_id: String,
+        // 연결이 끊겨서 나가는 경우 그 연결의 전송 채널. 먼저 "disconnected"를 알리고,
+        // 그 사이 다른 연결로 재접속했으면 무시함
+        disconnected: Option<MemberTx>,
+        reply: Reply<()>,
+    },
+    Resume {
+        tx: MemberTx,
+        player_id: String,
+        session_token: Secret,
+        reply: Reply<Result<(String, Subscription), ServerError>>,
+    },
+    Snapshot {
+        reply: Reply<RoomSnapshot>,
+    },
+    /// 관전 중계 태스크가 놓친 이벤트 대신 받아 가는 현재 게임 상태
+    GameStateSync {
+        reply: Reply<ServerMsg>,
+    },
+    Spectate {
+        tx: MemberTx,
+        invited: bool,
+        verified: Option<Arc<RoomPassword>>,
+        spectator_name: String,
+        spectator_id: String,
+        reply: Reply<Result<Subscription, ServerError>>,
+    },
+    LeaveSpectator {
+        spectator_id: String,
+    },
+    SetSpectatorPolicy {
+        player_id: String,
+        allow: Option<bool>,
+        max: Option<usize>,
+        delay: Option<SpectatorDelay>,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    SetReady {
+        player_id: String,
+        is_ready: bool,
+    },
+    ChooseSeat {
+        player_id: String,
+        side: char,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    SwapSides {
+        player_id: String,
+        accept: bool,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    StartGame {
+        player_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    OfferRematch {
+        player_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    AcceptRematch {
+        player_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    RollSticks {
+        player_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    MovePiece {
+        player_id: String,
+        mv: MoveRequest,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    PassTurn {
+        player_id: String,
+        roll: Option<u8>,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    ResetGame {
+        player_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    Chat {
+        sender_id: String,
+        channel: ChatChannel,
+        message: String,
+        message_type: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    SetChatPolicy {
+        player_id: String,
+        players_see_spectator_chat: Option<bool>,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    Delete {
+        player_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    TransferOwnership {
+        player_id: String,
+        target_id: String,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    Kick {
+        player_id: String,
+        target_id: String,
+        // 차단 목록에도 추가
+        ban: bool,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    UpdateSettings {
+        player_id: String,
+        update: RoomSettingsUpdate,
+        // Some(None)이면 비밀번호 해제, Some(Some(..))이면 새 비밀번호 (해싱은 방 태스크 밖에서)
+        password: Option<Option<Arc<RoomPassword>>>,
+        reply: Reply<Result<(), ServerError>>,
+    },
+    RegenerateInviteCode {
+        player_id: String,
+        reply: Reply<Result<String, ServerError>>,
+    },
+    Heartbeat {
+        player_id: String,
+    },
+}
+
+/// `UPDATE_ROOM_SETTINGS`로 바꿀 항목. `None`인 항목은 그대로 둡니다.
+#[derive(Debug, Default)]
+pub struct RoomSettingsUpdate {
+    pub room_name: Option<String>,
+    /// 빈 문자열이면 비밀번호 해제
+    pub password: Option<Secret>,
+    pub visibility: Option<Visibility>,
+    pub variant: Option<RuleVariant>,
+    pub clock: Option<ClockSettings>,
+    pub start_policy: Option<StartPolicy>,
+    pub best_of: Option<u32>,
+    pub allow_spectators: Option<bool>,
+    pub max_spectators: Option<usize>,
+    pub spectator_delay: Option<SpectatorDelay>,
+    pub players_see_spectator_chat: Option<bool>,
+}
+
+/// 명령을 보내고 응답을 기다립니다. 방 태스크가 이미 끝났으면 `ROOM_NOT_FOUND`.
+async fn request<T>(room: &Room, make: impl FnOnce(Reply<T>) -> RoomCmd) -> Result<T, ServerError> {
+    let (reply, rx) = oneshot::channel();
+    let not_found = || ServerError::RoomNotFound {
+        room_id: room.id.clone(),
+    };
+    room.cmd_tx
+        .send(make(reply))
+        .await
+        .map_err(|_| not_found())?;
+    rx.await.map_err(|_| not_found())
+}
+
+/// 응답이 필요 없는 명령
+async fn notify(room: &Room, cmd: RoomCmd) {
+    if room.cmd_tx.send(cmd).await.is_err() {
+        debug!("방 태스크가 이미 종료됨: {}", room.id);
+    }
+}
+
 // ========================= 방 관리 함수들 =========================
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_room(
     state: &AppState,
-    tx: mpsc::Sender<String>,
+    tx: MemberTx,
     room_name: String,
-    password: Option<String>,
+    password: Option<Secret>,
     max_players: usize,
+    spectator_policy: SpectatorPolicy,
+    variant: RuleVariant,
+    visibility: Visibility,
     player_name: String,
     player_id: String,
-) -> Result<Arc<Room>, String> {
+) -> Result<(Arc<Room>, Subscription), ServerError> {
+    if !state.drain.is_accepting() {
+        return Err(ServerError::ServerDraining);
+    }
     let room_id = Uuid::new_v4().to_string();
+    let invite_code = state.lobby.issue_invite_code(&room_id);
+    let password = match password {
+        Some(secret) => Some(RoomPassword::hash(secret).await),
+        None => None,
+    };
 
     // 방 생성
-    let (btx, _rx) = broadcast::channel::<ServerMsg>(256);
-    let room = Arc::new(Room {
-        id: room_id.clone(),
-        tx: btx.clone(),
-        inner: Arc::new(RwLock::new(RoomInner {
+    let (room, mut actor, cmd_rx) = new_room(
+        state,
+        room_id.clone(),
+        RoomInner {
             status: RoomStatus::Waiting,
-            name: room_name.clone(),
+            name: room_name,
             owner: player_id.clone(),
             password,
             max_players,
-            players: DashMap::new(),
-            spectators: DashMap::new(),
-            seats: DashMap::new(),
-            ready: DashMap::new(),
+            players: BTreeMap::new(),
+            spectators: BTreeMap::new(),
+            spectator_policy,
+            chat_policy: ChatPolicy::default(),
+            variant,
+            clock: ClockSettings::default(),
+            start_policy: StartPolicy::default(),
+            series: MatchSeries::new(1),
+            rematch_offer: None,
+            visibility,
+            invite_code,
+            seats: BTreeMap::new(),
+            swap_request: None,
+            banned: HashSet::new(),
+            ready: HashMap::new(),
             game: GameState::new(),
             game_id: Uuid::new_v4().to_string(),
+            game_started_at: ts(),
+            stats: GameStats::new('W'),
+            moves: Vec::new(),
+            last_game: None,
+            created_at: ts(),
+            updated_at: ts(),
             last_activity: ts(),
-        })),
-    });
-
-    // 방 ID를 키로 사용해서 저장
-    state.rooms.insert(room_id.clone(), room.clone());
+        },
+    );
 
-    // 생성과 동시에 방장 입장(W)
-    {
-        let mut inner = room.inner.write().await;
+    // 생성과 동시에 방장 입장(W) - 방 태스크가 시작되기 전이므로 직접 처리
+    let subscription = {
+        let inner = &mut actor.inner;
 
         // 방장의 고유한 표시명 생성 (방장은 항상 첫 번째이므로 중복 없음)
-        let unique_display_name = crate::types::generate_unique_display_name(&inner, &player_name);
+        let unique_display_name = crate::types::generate_unique_display_name(inner, &player_name);
 
         // 방장을 플레이어로 등록
-        let ptx = tx.clone();
         inner.players.insert(
             player_id.clone(),
             Player {
                 id: player_id.clone(),
                 name: unique_display_name.clone(),
-                tx: ptx,
+                tx: tx.clone(),
+                joined_at: ts(),
+                session_token: new_session_token(),
+                resume_deadline: None,
             },
         );
         inner.ready.insert(player_id.clone(), true);
@@ -71,435 +310,2659 @@ pub async fn create_room(
         let msg = ServerMsg::RoomCreated {
             room_id: room_id.clone(),
             room_name: inner.name.clone(),
-            owner: unique_display_name.clone(),
+            owner: unique_display_name,
             max_players: inner.max_players as u8,
             status: "waiting".into(),
+            visibility: inner.visibility,
+            invite_code: inner.invite_code.clone(),
         };
-        if let Err(e) = tx.send(msg.wrap()).await {
-            eprintln!("❌ ROOM_CREATED 메시지 전송 실패: {}", e);
-        }
+        send_to(&tx, msg);
 
         // 방장에게 ROOM_JOINED 메시지도 개별 전송 (플레이어 목록 업데이트용)
-        let players_json = crate::types::collect_players(&inner);
-        let current_player_info = players_json.iter().find(|p| {
-            p.get("playerId").and_then(|id| id.as_str()) == Some(&player_id)
-        }).cloned();
-        
-        let join_msg = ServerMsg::RoomJoined {
-            room_id: room_id.clone(),
-            room_name: inner.name.clone(),
-            players: players_json.clone(),
-            current_player: current_player_info,
-        };
-        if let Err(e) = tx.send(join_msg.wrap()).await {
-            eprintln!("❌ ROOM_JOINED 메시지 전송 실패: {}", e);
-        }
-
-        // 다른 플레이어들에게 브로드캐스트 (현재는 방장만 있으므로 의미없지만 일관성을 위해)
-        room.tx
-            .send(ServerMsg::RoomJoined {
-                room_id: room_id.clone(),
-                room_name: inner.name.clone(),
-                players: players_json,
-                current_player: None, // 다른 플레이어들에게는 현재 플레이어 정보 불필요
-            })
-            .ok();
-        inner.last_activity = ts();
-    }
+        actor.send_joined(&tx, &player_id);
+        actor.tx.subscribe()
+    };
 
-    Ok(room)
+    start_room(state, &room, actor, cmd_rx);
+    Ok((room, subscription))
 }
 
-pub async fn join_room(
+/// 방 핸들과 방 태스크를 만들고 관전자 중계를 시작합니다. 태스크는 `start_room`으로 돌립니다.
+fn new_room(
     state: &AppState,
-    tx: mpsc::Sender<String>,
     room_id: String,
-    password: Option<&str>,
-    player_name: String,
-    player_id: String,
-) -> Result<Arc<Room>, String> {
-    if let Some(room) = state.rooms.get(&room_id).map(|r| r.clone()) {
-        let mut inner = room.inner.write().await;
-
-        // 비밀번호 검증
-        if inner.password.as_deref().is_some() && inner.password.as_deref() != password {
-            return Err("INVALID_PASSWORD".to_string());
-        }
-
-        // 방이 가득 찼는지 확인
-        if inner.players.len() >= inner.max_players {
-            return Err("ROOM_FULL".to_string());
-        }
+    inner: RoomInner,
+) -> (Arc<Room>, RoomActor, mpsc::Receiver<RoomCmd>) {
+    let (btx, _rx) = broadcast::channel::<Arc<Outgoing>>(256);
+    let (spectator_tx, _srx) = broadcast::channel::<Arc<Outgoing>>(256);
+    let (cmd_tx, cmd_rx) = mpsc::channel::<RoomCmd>(ROOM_QUEUE_SIZE);
+    let (delay_tx, delay_rx) = watch::channel(inner.spectator_policy.delay);
+    let room = Arc::new(Room {
+        id: room_id.clone(),
+        tx: btx.clone(),
+        spectator_tx: spectator_tx.clone(),
+        cmd_tx,
+    });
 
-        // 이미 참가한 플레이어인지 확인
-        if inner.players.contains_key(&player_id) {
-            return Err("ALREADY_JOINED".to_string());
-        }
+    let actor = RoomActor {
+        id: room_id,
+        rooms: state.rooms.clone(),
+        lobby: state.lobby.clone(),
+        rolls: state.rolls.clone(),
+        games: state.games.clone(),
+        ratings: state.ratings.clone(),
+        drain: state.drain.clone(),
+        tx: btx,
+        spectator_tx,
+        delay_tx,
+        password_attempts: AttemptLimiter::new(ROOM_MAX_FAILURES),
+        closed: false,
+        inner,
+    };
 
-        // 플레이어의 고유한 표시명 생성
-        let unique_display_name = crate::types::generate_unique_display_name(&inner, &player_name);
+    // 관전자 중계 태스크 시작 (방 생성 직후부터 모든 브로드캐스트를 구독)
+    spawn_spectator_relay(&room, delay_rx);
+    (room, actor, cmd_rx)
+}
 
-        // 플레이어 등록 & 좌석 배정
-        inner.players.insert(
-            player_id.clone(),
-            Player {
-                id: player_id.clone(),
-                name: unique_display_name.clone(),
-                tx: tx.clone(),
-            },
-        );
-        inner.ready.insert(player_id.clone(), false);
+/// 방 ID를 키로 사용해서 저장하고 로비에 올린 뒤 방 태스크 시작
+fn start_room(
+    state: &AppState,
+    room: &Arc<Room>,
+    actor: RoomActor,
+    cmd_rx: mpsc::Receiver<RoomCmd>,
+) {
+    state.rooms.insert(room.id.clone(), room.clone());
+    state.lobby.upsert(actor.summary());
+    tokio::spawn(actor.run(cmd_rx));
+}
 
-        // 빈 좌석에 배정
-        if !inner.seats.contains_key(&'W') {
-            inner.seats.insert('W', player_id.clone());
-        } else if !inner.seats.contains_key(&'B') {
-            inner.seats.insert('B', player_id.clone());
-        } else {
-            // 좌석이 모두 찬 경우 (이론적으로는 발생하지 않아야 함)
-            return Err("NO_AVAILABLE_SEATS".to_string());
-        }
+fn new_session_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
 
-        // 새로 참가한 플레이어에게 개별 메시지 전송
-        let players_json = crate::types::collect_players(&inner);
-        let current_player_info = players_json.iter().find(|p| {
-            p.get("playerId").and_then(|id| id.as_str()) == Some(&player_id)
-        }).cloned();
-        
-        let join_msg = ServerMsg::RoomJoined {
-            room_id: room.id.clone(),
-            room_name: inner.name.clone(),
-            players: players_json.clone(),
-            current_player: current_player_info,
-        };
-        if let Err(e) = tx.send(join_msg.wrap()).await {
-            eprintln!("❌ ROOM_JOINED 메시지 전송 실패: {}", e);
-        }
+/// 스냅샷에서 방을 되살립니다. 플레이어는 모두 연결이 끊긴 상태로 시작하고,
+/// `grace` 안에 세션 토큰으로 돌아오지 않으면 자리에서 빠집니다. 관전자는 복원하지 않습니다.
+pub fn restore_room(state: &AppState, snap: RoomSnapshot, grace: Duration) {
+    let deadline = ts() + grace.as_millis() as u64;
+    let invite_code = if state
+        .lobby
+        .register_invite_code(&snap.invite_code, &snap.id)
+    {
+        snap.invite_code
+    } else {
+        state.lobby.issue_invite_code(&snap.id)
+    };
+    // 아무도 받지 않는 채널 - 다시 접속하기 전까지 개인 메시지는 버려짐
+    let (offline_tx, _) = mpsc::channel(1);
+    let players = snap
+        .players
+        .into_iter()
+        .map(|p| {
+            let player = Player {
+                id: p.id.clone(),
+                name: p.name,
+                tx: offline_tx.clone(),
+                joined_at: p.joined_at,
+                session_token: p.session_token,
+                resume_deadline: Some(deadline),
+            };
+            (p.id, player)
+        })
+        .collect();
+
+    let (room, actor, cmd_rx) = new_room(
+        state,
+        snap.id.clone(),
+        RoomInner {
+            status: snap.status,
+            name: snap.name,
+            owner: snap.owner,
+            password: snap.password_hash.map(RoomPassword::from_phc),
+            max_players: snap.max_players,
+            players,
+            spectators: BTreeMap::new(),
+            spectator_policy: snap.spectator_policy,
+            chat_policy: snap.chat_policy,
+            variant: snap.variant,
+            clock: snap.clock,
+            start_policy: snap.start_policy,
+            series: snap.series,
+            rematch_offer: snap.rematch_offer,
+            visibility: snap.visibility,
+            invite_code,
+            seats: snap.seats,
+            swap_request: snap.swap_request,
+            banned: snap.banned,
+            ready: snap.ready,
+            game: snap.game,
+            game_id: snap.game_id,
+            game_started_at: snap.game_started_at,
+            stats: snap.stats,
+            moves: snap.moves,
+            last_game: snap.last_game,
+            created_at: snap.created_at,
+            updated_at: ts(),
+            last_activity: ts(),
+        },
+    );
+    info!(
+        "♻️ 방 복원: {} (상태={}, 플레이어 {}명)",
+        room.id,
+        actor.inner.status.as_str(),
+        actor.inner.players.len()
+    );
+    start_room(state, &room, actor, cmd_rx);
+}
 
-        // 다른 플레이어들에게 브로드캐스트
-        room.tx
-            .send(ServerMsg::RoomJoined {
-                room_id: room.id.clone(),
-                room_name: inner.name.clone(),
-                players: players_json,
-                current_player: None, // 다른 플레이어들에게는 현재 플레이어 정보 불필요
-            })
-            .ok();
-        inner.last_activity = ts();
+/// 방의 현재 상태를 스냅샷으로 받습니다. 방 태스크가 이미 끝났으면 `None`.
+pub async fn snapshot_room(room: &Room) -> Option<RoomSnapshot> {
+    request(room, |reply| RoomCmd::Snapshot { reply })
+        .await
+        .ok()
+}
 
-        drop(inner); // 🔑 가변 대여 해제
+/// 세션 토큰으로 플레이어 자리에 다시 연결합니다. 성공하면 `SESSION_RESUMED`를 받습니다.
+pub async fn resume_session(
+    state: &AppState,
+    tx: MemberTx,
+    room_id: String,
+    player_id: String,
+    session_token: Secret,
+) -> Result<(Arc<Room>, String, Subscription), ServerError> {
+    let (room, _) = find_room(state, RoomRef::Id(room_id))?;
+    let (player_name, subscription) = request(&room, |reply| RoomCmd::Resume {
+        tx,
+        player_id,
+        session_token,
+        reply,
+    })
+    .await??;
+    Ok((room, player_name, subscription))
+}
 
-        Ok(room)
-    } else {
-        Err("ROOM_NOT_FOUND".to_string())
+/// 방 ID나 초대 코드로 방을 찾습니다. 초대 코드로 찾았으면 `true`를 함께 돌려줍니다.
+fn find_room(state: &AppState, target: RoomRef) -> Result<(Arc<Room>, bool), ServerError> {
+    let (room_id, invited) = match target {
+        RoomRef::Id(room_id) => (room_id, false),
+        RoomRef::InviteCode(invite_code) => match state.lobby.room_for_invite(&invite_code) {
+            Some(room_id) => (room_id, true),
+            None => return Err(ServerError::InvalidInviteCode { invite_code }),
+        },
+    };
+    match state.rooms.get(&room_id) {
+        Some(room) => Ok((room.clone(), invited)),
+        None => Err(ServerError::RoomNotFound { room_id }),
     }
 }
 
-pub async fn leave_room(room: &Arc<Room>, player_id: String) -> bool {
-    let mut inner = room.inner.write().await;
-
-    // 플레이어 정보 저장 (알림용)
-    let _player_name = inner
-        .players
-        .get(&player_id)
-        .map(|p| p.name.clone())
-        .unwrap_or_else(|| "Unknown".into());
+/// 비밀번호가 걸린 방이면 방 태스크 밖(블로킹 스레드)에서 해시를 검증합니다.
+/// 검증한 해시를 입장 명령에 실어 보내면, 방 태스크는 그 사이 비밀번호가 바뀌지 않았는지만 확인합니다.
+async fn check_password(
+    room: &Arc<Room>,
+    password: Option<Secret>,
+) -> Result<Option<Arc<RoomPassword>>, ServerError> {
+    let Some(hash) = request(room, |reply| RoomCmd::PasswordGate { reply }).await?? else {
+        return Ok(None);
+    };
+    let Some(secret) = password.filter(|p| !p.is_empty()) else {
+        return Err(ServerError::InvalidPassword);
+    };
+    if !hash.verify(secret).await {
+        notify(room, RoomCmd::PasswordFailed).await;
+        return Err(ServerError::InvalidPassword);
+    }
+    Ok(Some(hash))
+}
 
-    // 플레이어 제거
-    inner.players.remove(&player_id);
-    inner.ready.remove(&player_id);
+pub async fn join_room(
+    state: &AppState,
+    tx: MemberTx,
+    target: RoomRef,
+    password: Option<Secret>,
+    player_name: String,
+    player_id: String,
+) -> Result<(Arc<Room>, Subscription), ServerError> {
+    let (room, invited) = find_room(state, target)?;
+    let verified = check_password(&room, password).await?;
+    let subscription = request(&room, |reply| RoomCmd::Join {
+        tx,
+        invited,
+        verified,
+        player_name,
+        player_id,
+        reply,
+    })
+    .await??;
+    Ok((room, subscription))
+}
 
-    // 좌석에서 제거
-    for k in ['W', 'B'] {
-        if inner.seats.get(&k).map(|e| e.value().clone()) == Some(player_id.clone()) {
-            inner.seats.remove(&k);
-        }
-    }
+/// 플레이어를 방에서 내보냅니다. 마지막 플레이어가 나가면 방 태스크가 방을 삭제합니다.
+pub async fn leave_room(room: &Arc<Room>, player_id: String, disconnected: Option<MemberTx>) {
+    request(room, |reply| RoomCmd::Leave {
+        player_id,
+        disconnected,
+        reply,
+    })
+    .await
+    .ok();
+}
 
-    // 방장이 나간 경우 새로운 방장 지정
-    if inner.owner == player_id && !inner.players.is_empty() {
-        let new_owner_id = inner
-            .players
-            .iter()
-            .next()
-            .map(|entry| entry.key().clone())
-            .unwrap_or_else(|| player_id.clone());
-        inner.owner = new_owner_id.clone();
+pub async fn spectate_room(
+    state: &AppState,
+    tx: MemberTx,
+    target: RoomRef,
+    password: Option<Secret>,
+    spectator_name: String,
+    spectator_id: String,
+) -> Result<(Arc<Room>, Subscription), ServerError> {
+    let (room, invited) = find_room(state, target)?;
+    let verified = check_password(&room, password).await?;
+    let subscription = request(&room, |reply| RoomCmd::Spectate {
+        tx,
+        invited,
+        verified,
+        spectator_name,
+        spectator_id,
+        reply,
+    })
+    .await??;
+    Ok((room, subscription))
+}
 
-        // 새로운 방장 지정 알림
-        room.tx
-            .send(ServerMsg::PlayerStatus {
-                room_id: room.id.clone(),
-                player_id: new_owner_id,
-                status: "new_owner".into(),
-                last_seen: ts(),
-            })
-            .ok();
-    }
-
-    // 게임 중이었다면 게임 종료 처리 및 게임 정보 초기화
-    if inner.status == RoomStatus::Playing {
-        inner.status = RoomStatus::Waiting;
-        
-        // 게임 정보 초기화
-        inner.game = crate::game::GameState::new();
-        inner.game_id = uuid::Uuid::new_v4().to_string();
-        
-        // 모든 플레이어의 준비 상태 초기화
-        for player_entry in inner.players.iter() {
-            let player_id = player_entry.key().clone();
-            inner.ready.insert(player_id, false);
-        }
-        
-        room.tx
-            .send(ServerMsg::PlayerStatus {
-                room_id: room.id.clone(),
-                player_id: "system".to_string(),
-                status: "game_cancelled".into(),
-                last_seen: ts(),
-            })
-            .ok();
-    }
+pub async fn leave_spectator(room: &Arc<Room>, spectator_id: String) {
+    notify(room, RoomCmd::LeaveSpectator { spectator_id }).await;
+}
 
-    inner.last_activity = ts();
+/// 주어진 항목만 바꾸고 나머지는 현재 정책을 유지합니다.
+pub async fn set_spectator_policy(
+    room: &Arc<Room>,
+    player_id: String,
+    allow: Option<bool>,
+    max: Option<usize>,
+    delay: Option<SpectatorDelay>,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::SetSpectatorPolicy {
+        player_id,
+        allow,
+        max,
+        delay,
+        reply,
+    })
+    .await?
+}
 
-    // 남은 플레이어들에게 플레이어 나감 알림
-    room.tx
-        .send(ServerMsg::PlayerStatus {
-            room_id: room.id.clone(),
-            player_id: player_id.clone(),
-            status: "left_room".into(),
-            last_seen: ts(),
-        })
-        .ok();
+pub async fn set_ready(room: &Arc<Room>, player_id: String, is_ready: bool) {
+    notify(
+        room,
+        RoomCmd::SetReady {
+            player_id,
+            is_ready,
+        },
+    )
+    .await;
+}
 
-    // 방 정보 업데이트 알림
-    let players_json = crate::types::collect_players(&inner);
-    room.tx
-        .send(ServerMsg::RoomJoined {
-            room_id: room.id.clone(),
-            room_name: inner.name.clone(),
-            players: players_json,
-            current_player: None, // 방 정보 업데이트에는 현재 플레이어 정보 불필요
-        })
-        .ok();
+pub async fn send_chat(
+    room: &Arc<Room>,
+    sender_id: String,
+    channel: ChatChannel,
+    message: String,
+    message_type: String,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::Chat {
+        sender_id,
+        channel,
+        message,
+        message_type,
+        reply,
+    })
+    .await?
+}
 
-    // 방이 비어있는지 확인하여 반환
-    inner.players.is_empty()
+pub async fn set_chat_policy(
+    room: &Arc<Room>,
+    player_id: String,
+    players_see_spectator_chat: Option<bool>,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::SetChatPolicy {
+        player_id,
+        players_see_spectator_chat,
+        reply,
+    })
+    .await?
 }
 
 pub async fn delete_room(
     state: &AppState,
     room_id: String,
     player_id: String,
-) -> Result<(), String> {
-    if let Some(room) = state.rooms.get(&room_id).map(|r| r.clone()) {
-        let inner = room.inner.read().await;
-        if inner.owner != player_id {
-            return Err("NOT_ROOM_OWNER".to_string());
-        }
-        drop(inner);
+) -> Result<(), ServerError> {
+    let Some(room) = state.rooms.get(&room_id).map(|r| r.clone()) else {
+        return Err(ServerError::RoomNotFound { room_id });
+    };
+    request(&room, |reply| RoomCmd::Delete { player_id, reply }).await?
+}
 
-        // 방 삭제 전에 모든 플레이어들에게 알림
-        room.tx
-            .send(ServerMsg::PlayerStatus {
-                room_id: room_id.clone(),
-                player_id: "system".to_string(),
-                status: "room_deleted".into(),
-                last_seen: ts(),
-            })
-            .ok();
+/// 방장이 다른 플레이어에게 방장을 넘깁니다.
+pub async fn transfer_ownership(
+    room: &Arc<Room>,
+    player_id: String,
+    target_id: String,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::TransferOwnership {
+        player_id,
+        target_id,
+        reply,
+    })
+    .await?
+}
 
-        // 방을 상태에서 제거
-        state.rooms.remove(&room_id);
-        println!("🗑️ 방 삭제 완료: {}", room_id);
+/// 방장이 플레이어나 관전자를 내보냅니다. `ban`이면 새 연결로도 같은 ID로는 다시 들어오지 못하게 막고,
+/// 차단 목록은 스냅샷에 남아 재시작 뒤에도 유지됩니다.
+pub async fn kick_player(
+    room: &Arc<Room>,
+    player_id: String,
+    target_id: String,
+    ban: bool,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::Kick {
+        player_id,
+        target_id,
+        ban,
+        reply,
+    })
+    .await?
+}
 
-        Ok(())
-    } else {
-        Err("ROOM_NOT_FOUND".to_string())
-    }
+/// 방장이 방 설정을 바꿉니다. 새 비밀번호는 방 태스크에 넘기기 전에 해싱합니다.
+pub async fn update_room_settings(
+    room: &Arc<Room>,
+    player_id: String,
+    mut update: RoomSettingsUpdate,
+) -> Result<(), ServerError> {
+    let password = match update.password.take() {
+        Some(secret) if secret.is_empty() => Some(None),
+        Some(secret) => Some(Some(RoomPassword::hash(secret).await)),
+        None => None,
+    };
+    request(room, |reply| RoomCmd::UpdateSettings {
+        player_id,
+        update,
+        password,
+        reply,
+    })
+    .await?
 }
 
-pub async fn start_game(room: &Arc<Room>, player_id: String) -> Result<(), String> {
-    let mut inner = room.inner.write().await;
+/// 초대 코드를 새로 발급합니다. 예전 코드는 바로 폐기됩니다.
+pub async fn regenerate_invite_code(
+    room: &Arc<Room>,
+    player_id: String,
+) -> Result<String, ServerError> {
+    request(room, |reply| RoomCmd::RegenerateInviteCode {
+        player_id,
+        reply,
+    })
+    .await?
+}
 
-    if inner.owner != player_id {
-        return Err("NOT_ROOM_OWNER".to_string());
-    }
+/// 빈 좌석으로 옮깁니다.
+pub async fn choose_seat(
+    room: &Arc<Room>,
+    player_id: String,
+    side: char,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::ChooseSeat {
+        player_id,
+        side,
+        reply,
+    })
+    .await?
+}
 
-    if inner.seats.len() != 2 {
-        return Err("NEED_TWO_PLAYERS".to_string());
-    }
+/// 진영 교체를 요청하거나 상대의 요청에 동의합니다. `accept`가 false면 요청을 취소/거절합니다.
+pub async fn swap_sides(
+    room: &Arc<Room>,
+    player_id: String,
+    accept: bool,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::SwapSides {
+        player_id,
+        accept,
+        reply,
+    })
+    .await?
+}
 
-    // 모든 플레이어가 준비되었는지 확인
-    let all_ready = inner.players.iter().all(|p| {
-        inner
-            .ready
-            .get(p.key())
-            .map(|r| *r.value())
-            .unwrap_or(false)
-    });
+pub async fn start_game(room: &Arc<Room>, player_id: String) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::StartGame { player_id, reply }).await?
+}
 
-    if !all_ready {
-        return Err("PLAYERS_NOT_READY".to_string());
-    }
+pub async fn roll_sticks(room: &Arc<Room>, player_id: String) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::RollSticks { player_id, reply }).await?
+}
+
+pub async fn move_piece(
+    room: &Arc<Room>,
+    player_id: String,
+    mv: MoveRequest,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::MovePiece {
+        player_id,
+        mv,
+        reply,
+    })
+    .await?
+}
 
-    inner.status = RoomStatus::Playing;
-    inner.game = GameState::new();
-    inner.game_id = Uuid::new_v4().to_string();
-    let players = crate::types::collect_players(&inner);
-    let gs = inner.game.snapshot();
+pub async fn pass_turn(
+    room: &Arc<Room>,
+    player_id: String,
+    roll: Option<u8>,
+) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::PassTurn {
+        player_id,
+        roll,
+        reply,
+    })
+    .await?
+}
 
-    // 게임 시작 메시지 브로드캐스트
-    let game_started_msg = ServerMsg::GameStarted {
-        room_id: room.id.clone(),
-        game_id: inner.game_id.clone(),
-        players: players.clone(),
-        initial_turn: inner.game.turn.to_string(),
-        game_state: serde_json::to_value(gs).unwrap(),
-    };
+pub async fn offer_rematch(room: &Arc<Room>, player_id: String) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::OfferRematch { player_id, reply }).await?
+}
 
-    if let Err(e) = room.tx.send(game_started_msg) {
-        eprintln!("❌ GAME_STARTED 브로드캐스트 실패: {}", e);
-    }
+pub async fn accept_rematch(room: &Arc<Room>, player_id: String) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::AcceptRematch { player_id, reply }).await?
+}
 
-    inner.last_activity = ts();
+pub async fn reset_game(room: &Arc<Room>, player_id: String) -> Result<(), ServerError> {
+    request(room, |reply| RoomCmd::ResetGame { player_id, reply }).await?
+}
 
-    Ok(())
+pub async fn heartbeat(room: &Arc<Room>, player_id: String) {
+    notify(room, RoomCmd::Heartbeat { player_id }).await;
 }
 
-pub async fn reset_game(room: &Arc<Room>, player_id: String) -> Result<(), String> {
-    let mut inner = room.inner.write().await;
+/// 로비 색인에서 바로 읽으므로 바쁜 방이 있어도 기다리거나 빠뜨리지 않습니다.
+pub fn get_room_list(
+    state: &AppState,
+    filters: serde_json::Value,
+) -> Result<ServerMsg, ServerError> {
+    let query = RoomQuery::from_json(&filters)?;
+    debug!(
+        "🔍 get_room_list 호출됨 - 조건: {:?}, 현재 저장된 방 수: {}",
+        query,
+        state.lobby.len()
+    );
+
+    let page = state.lobby.page(&query);
+    Ok(ServerMsg::RoomList {
+        rooms: page.rooms,
+        total_count: page.total_count,
+        next_cursor: page.next_cursor,
+        filters,
+    })
+}
 
-    if inner.owner != player_id {
-        return Err("NOT_ROOM_OWNER".to_string());
+/// 입장 요청이 검증한 해시가 지금 방의 비밀번호와 같은지
+fn password_verified(
+    current: &Option<Arc<RoomPassword>>,
+    verified: &Option<Arc<RoomPassword>>,
+) -> bool {
+    match (current, verified) {
+        (None, _) => true,
+        (Some(current), Some(verified)) => Arc::ptr_eq(current, verified),
+        (Some(_), None) => false,
     }
+}
 
-    // 게임이 진행 중이거나 완료된 상태에서만 리셋 가능
-    if inner.status != RoomStatus::Playing && inner.status != RoomStatus::Finished {
-        return Err("GAME_NOT_IN_PROGRESS".to_string());
+/// 방 태스크에서 개별 멤버에게 보냅니다. 느린 연결 때문에 방 전체가 멈추지 않도록 기다리지 않습니다.
+fn send_to(tx: &MemberTx, msg: ServerMsg) {
+    if let Err(e) = tx.try_send(Outgoing::new(msg)) {
+        warn!("❌ 개별 메시지 전송 실패: {}", e);
     }
+}
 
-    let old = inner.game_id.clone();
-    inner.game = GameState::new();
-    inner.game_id = Uuid::new_v4().to_string();
+// ========================= 방 태스크 =========================
+
+/// 방 하나의 상태를 소유하고 명령을 순서대로 처리하는 태스크
+struct RoomActor {
+    id: String,
+    rooms: Arc<DashMap<String, Arc<Room>>>,
+    lobby: Arc<Lobby>,
+    // 서버 전체 굴림 집계 (공정성 검정용)
+    rolls: Arc<RollAudit>,
+    // 끝난 게임 기록 저장소
+    games: Arc<dyn GameRepository>,
+    // 플레이어 레이팅 (게임이 끝날 때 갱신, 로비 정렬에 사용)
+    ratings: Arc<Ratings>,
+    // 드레인 중에는 새 게임을 시작하지 않음
+    drain: Arc<Drain>,
+    tx: broadcast::Sender<Arc<Outgoing>>,
+    spectator_tx: broadcast::Sender<Arc<Outgoing>>,
+    // 관전 중계 태스크에 지연 설정 변경을 알림
+    delay_tx: watch::Sender<SpectatorDelay>,
+    // 이 방에 대한 비밀번호 실패 횟수 (모든 연결 합산)
+    password_attempts: AttemptLimiter,
+    closed: bool,
+    inner: RoomInner,
+}
 
-    room.tx
-        .send(ServerMsg::GameReset {
-            room_id: room.id.clone(),
-            game_id: old,
-            reset_by: player_id.clone(),
-            new_game_id: inner.game_id.clone(),
-        })
-        .ok();
+impl RoomActor {
+    async fn run(mut self, mut rx: mpsc::Receiver<RoomCmd>) {
+        info!("🏠 방 태스크 시작: {}", self.id);
+        let mut listed = self.summary();
+        loop {
+            // 재시작 후 돌아오지 않은 플레이어가 있으면 가장 이른 유예 마감에 깨어남
+            let deadline = self.next_resume_deadline();
+            let wait = Duration::from_millis(deadline.unwrap_or(0).saturating_sub(ts()));
+            tokio::select! {
+                cmd = rx.recv() => match cmd {
+                    Some(cmd) => self.handle(cmd),
+                    None => break,
+                },
+                _ = tokio::time::sleep(wait), if deadline.is_some() => self.expire_sessions(),
+            }
+            if self.closed {
+                break;
+            }
 
-    let gs = inner.game.snapshot();
-    room.tx
-        .send(ServerMsg::GameStarted {
-            room_id: room.id.clone(),
-            game_id: inner.game_id.clone(),
-            players: crate::types::collect_players(&inner),
-            initial_turn: inner.game.turn.to_string(),
-            game_state: serde_json::to_value(gs).unwrap(),
-        })
-        .ok();
+            // 목록에 보이는 정보가 바뀌었을 때만 updated_at을 올리고 로비에 알림
+            let summary = self.summary();
+            if summary != listed {
+                self.inner.updated_at = ts();
+                listed = self.summary();
+                self.lobby.upsert(listed.clone());
+            }
+        }
+        info!("🏁 방 태스크 종료: {}", self.id);
+    }
 
-    inner.status = RoomStatus::Playing;
-    inner.last_activity = ts();
+    fn handle(&mut self, cmd: RoomCmd) {
+        self.inner.last_activity = ts();
 
-    Ok(())
-}
+        match cmd {
+            RoomCmd::PasswordGate { reply } => {
+                let result = self
+                    .password_attempts
+                    .check()
+                    .map(|_| self.inner.password.clone());
+                reply.send(result).ok();
+            }
+            RoomCmd::PasswordFailed => {
+                self.password_attempts.record_failure();
+                if self.password_attempts.check().is_err() {
+                    warn!("🔒 비밀번호 실패가 많아 방 입장을 잠시 잠금: {}", self.id);
+                }
+            }
+            RoomCmd::Join {
+                tx,
+                invited,
+                verified,
+                player_name,
+                player_id,
+                reply,
+            } => {
+                let result = self.join(tx, invited, verified, player_name, player_id);
+                reply.send(result).ok();
+            }
+            RoomCmd::Leave {
+                player_id,
+                disconnected,
+                reply,
+            } => {
+                if let Some(tx) = disconnected {
+                    match self.inner.players.get(&player_id) {
+                        // 강퇴/차단으로 이미 방에서 빠진 플레이어 - 끊김을 알릴 필요 없음
+                        None => {
+                            reply.send(()).ok();
+                            return;
+                        }
+                        Some(p) if !p.tx.same_channel(&tx) => {
+                            debug!("이전 연결 종료 무시 (이미 재접속함): {}", player_id);
+                            reply.send(()).ok();
+                            return;
+                        }
+                        Some(_) => {}
+                    }
+                    // 다른 플레이어들에게 연결 끊김 알림
+                    self.broadcast(ServerMsg::PlayerStatus {
+                        room_id: self.id.clone(),
+                        player_id: player_id.clone(),
+                        status: "disconnected".into(),
+                        last_seen: ts(),
+                        message: None,
+                    });
+                }
+                self.leave(player_id, "left_room");
+                reply.send(()).ok();
+            }
+            RoomCmd::Resume {
+                tx,
+                player_id,
+                session_token,
+                reply,
+            } => {
+                reply.send(self.resume(tx, &player_id, &session_token)).ok();
+            }
+            RoomCmd::Snapshot { reply } => {
+                reply.send(self.snapshot()).ok();
+            }
+            RoomCmd::GameStateSync { reply } => {
+                reply.send(self.game_state_sync()).ok();
+            }
+            RoomCmd::Spectate {
+                tx,
+                invited,
+                verified,
+                spectator_name,
+                spectator_id,
+                reply,
+            } => {
+                let result = self.spectate(tx, invited, verified, spectator_name, spectator_id);
+                reply.send(result).ok();
+            }
+            RoomCmd::LeaveSpectator { spectator_id } => self.leave_spectator(&spectator_id),
+            RoomCmd::SetSpectatorPolicy {
+                player_id,
+                allow,
+                max,
+                delay,
+                reply,
+            } => {
+                let result = self.set_spectator_policy(&player_id, allow, max, delay);
+                reply.send(result).ok();
+            }
+            RoomCmd::SetReady {
+                player_id,
+                is_ready,
+            } => self.set_ready(player_id, is_ready),
+            RoomCmd::ChooseSeat {
+                player_id,
+                side,
+                reply,
+            } => {
+                reply.send(self.choose_seat(&player_id, side)).ok();
+            }
+            RoomCmd::SwapSides {
+                player_id,
+                accept,
+                reply,
+            } => {
+                reply.send(self.swap_sides(player_id, accept)).ok();
+            }
+            RoomCmd::StartGame { player_id, reply } => {
+                reply.send(self.start_game(&player_id)).ok();
+            }
+            RoomCmd::OfferRematch { player_id, reply } => {
+                reply.send(self.offer_rematch(player_id)).ok();
+            }
+            RoomCmd::AcceptRematch { player_id, reply } => {
+                reply.send(self.accept_rematch(&player_id)).ok();
+            }
+            RoomCmd::RollSticks { player_id, reply } => {
+                reply.send(self.roll_sticks(player_id)).ok();
+            }
+            RoomCmd::MovePiece {
+                player_id,
+                mv,
+                reply,
+            } => {
+                reply.send(self.move_piece(player_id, mv)).ok();
+            }
+            RoomCmd::PassTurn {
+                player_id,
+                roll,
+                reply,
+            } => {
+                reply.send(self.pass_turn(&player_id, roll)).ok();
+            }
+            RoomCmd::ResetGame { player_id, reply } => {
+                reply.send(self.reset_game(player_id)).ok();
+            }
+            RoomCmd::Chat {
+                sender_id,
+                channel,
+                message,
+                message_type,
+                reply,
+            } => {
+                let result = self.chat(sender_id, channel, message, message_type);
+                reply.send(result).ok();
+            }
+            RoomCmd::SetChatPolicy {
+                player_id,
+                players_see_spectator_chat,
+                reply,
+            } => {
+                let result = self.set_chat_policy(&player_id, players_see_spectator_chat);
+                reply.send(result).ok();
+            }
+            RoomCmd::Delete { player_id, reply } => {
+                let result = if self.inner.owner != player_id {
+                    Err(ServerError::NotRoomOwner)
+                } else {
+                    self.close();
+                    Ok(())
+                };
+                reply.send(result).ok();
+            }
+            RoomCmd::TransferOwnership {
+                player_id,
+                target_id,
+                reply,
+            } => {
+                reply
+                    .send(self.transfer_ownership(&player_id, target_id))
+                    .ok();
+            }
+            RoomCmd::Kick {
+                player_id,
+                target_id,
+                ban,
+                reply,
+            } => {
+                reply.send(self.kick(&player_id, target_id, ban)).ok();
+            }
+            RoomCmd::UpdateSettings {
+                player_id,
+                update,
+                password,
+                reply,
+            } => {
+                reply
+                    .send(self.update_settings(player_id, update, password))
+                    .ok();
+            }
+            RoomCmd::RegenerateInviteCode { player_id, reply } => {
+                reply.send(self.regenerate_invite_code(&player_id)).ok();
+            }
+            RoomCmd::Heartbeat { player_id } => {
+                self.broadcast(ServerMsg::PlayerStatus {
+                    room_id: self.id.clone(),
+                    player_id,
+                    status: "connected".into(),
+                    last_seen: self.inner.last_activity,
+                    message: None,
+                });
+            }
+        }
+    }
 
-pub async fn get_room_list(state: &AppState, filters: serde_json::Value) -> ServerMsg {
-    let status_filter = filters
-        .get("status")
-        .and_then(|x| x.as_str())
-        .unwrap_or("all");
-    let has_password_filter = filters.get("hasPassword");
-    let max_players_filter = filters.get("maxPlayers");
+    fn broadcast(&self, msg: ServerMsg) -> usize {
+        self.tx.send(Outgoing::new(msg)).unwrap_or(0)
+    }
 
-    println!("🔍 get_room_list 호출됨 - 필터: {:?}", filters);
-    println!("🔍 상태 필터: {}, 비밀번호 필터: {:?}, 최대 플레이어 필터: {:?}", 
-             status_filter, has_password_filter, max_players_filter);
-    println!("🔍 현재 저장된 방 수: {}", state.rooms.len());
+    /// 방 구성원 목록이 바뀌었음을 알림
+    fn broadcast_members(&self) {
+        self.broadcast(ServerMsg::RoomJoined {
+            room_id: self.id.clone(),
+            room_name: self.inner.name.clone(),
+            players: crate::types::collect_players(&self.inner),
+            spectators: crate::types::collect_spectators(&self.inner),
+            current_player: None, // 다른 플레이어들에게는 현재 플레이어 정보 불필요
+            session_token: None,
+        });
+    }
 
-    let mut rooms = vec![];
+    /// 새로 들어온 플레이어에게 자신의 정보가 담긴 ROOM_JOINED를 보내고, 다른 구성원에게도 알림
+    fn send_joined(&self, tx: &MemberTx, player_id: &str) {
+        let players_json = crate::types::collect_players(&self.inner);
+        let current_player_info = players_json
+            .iter()
+            .find(|p| p.get("playerId").and_then(|id| id.as_str()) == Some(player_id))
+            .cloned();
+
+        send_to(
+            tx,
+            ServerMsg::RoomJoined {
+                room_id: self.id.clone(),
+                room_name: self.inner.name.clone(),
+                players: players_json,
+                spectators: crate::types::collect_spectators(&self.inner),
+                current_player: current_player_info,
+                session_token: self
+                    .inner
+                    .players
+                    .get(player_id)
+                    .map(|p| p.session_token.clone()),
+            },
+        );
+        self.broadcast_members();
+    }
 
-    for room_entry in state.rooms.iter() {
-        let room = room_entry.value();
-        let inner = room.inner.try_read();
+    /// 방을 삭제합니다. 남아있는 구성원에게 알리고 방 목록에서 뺀 뒤 태스크를 끝냅니다.
+    fn close(&mut self) {
+        self.broadcast(ServerMsg::PlayerStatus {
+            room_id: self.id.clone(),
+            player_id: "system".to_string(),
+            status: "room_deleted".into(),
+            last_seen: ts(),
+            message: None,
+        });
+        self.rooms.remove(&self.id);
+        self.lobby.remove(&self.id);
+        self.lobby.revoke_invite_code(&self.inner.invite_code);
+        self.closed = true;
+        info!("🗑️ 방 삭제 완료: {}", self.id);
+    }
 
-        if let Ok(inner) = inner {
-            println!("🔍 방 처리 중: ID={}, 이름={}, 상태={:?}, 비밀번호={:?}, 플레이어수={}", 
-                     room.id, inner.name, inner.status, inner.password, inner.players.len());
-            // 상태 필터링
-            let status_match = match status_filter {
-                "waiting" => matches!(inner.status, RoomStatus::Waiting),
-                "playing" => matches!(inner.status, RoomStatus::Playing),
-                _ => true, // "all" or 기타
-            };
+    fn summary(&self) -> RoomSummary {
+        let inner = &self.inner;
+        RoomSummary {
+            id: self.id.clone(),
+            name: inner.name.clone(),
+            status: inner.status,
+            owner: inner.owner.clone(),
+            current_players: inner.players.len(),
+            max_players: inner.max_players,
+            spectator_count: inner.spectators.len(),
+            allow_spectators: inner.spectator_policy.allow,
+            max_spectators: inner.spectator_policy.max,
+            spectator_delay: inner.spectator_policy.delay.to_json(),
+            has_password: inner.password.is_some(),
+            variant: inner.variant,
+            clock: inner.clock,
+            visibility: inner.visibility,
+            created_at: inner.created_at,
+            updated_at: inner.updated_at,
+            rating: self.rating(),
+        }
+    }
 
-            println!("🔍 상태 필터링: 방={}, 상태={:?}, 필터={}, 매치={}", 
-                     room.id, inner.status, status_filter, status_match);
+    /// 앉은 플레이어들의 평균 레이팅. 게임이 끝나 점수가 바뀌면 다음 요약에 반영됩니다.
+    fn rating(&self) -> u32 {
+        let seated = &self.inner.seats;
+        if seated.is_empty() {
+            return 0;
+        }
+        let total: u32 = seated.values().map(|pid| self.ratings.get(pid)).sum();
+        total / seated.len() as u32
+    }
 
-            if !status_match {
-                println!("❌ 상태 필터로 인해 제외됨: {}", room.id);
-                continue;
-            }
+    /// 관전 중계 재동기화용 현재 게임 상태 (대기 중이면 보드 없음)
+    fn game_state_sync(&self) -> ServerMsg {
+        let inner = &self.inner;
+        let game_state = (inner.status != RoomStatus::Waiting)
+            .then(|| serde_json::to_value(inner.game.snapshot()).unwrap());
+        ServerMsg::GameStateSync {
+            room_id: self.id.clone(),
+            game_id: inner.game_id.clone(),
+            status: inner.status,
+            game_state,
+        }
+    }
 
-            // 비밀번호 필터링
-            let password_match = match has_password_filter {
-                Some(serde_json::Value::Bool(has_pass)) => inner.password.is_some() == *has_pass,
-                _ => true, // null이거나 다른 값이면 모두 포함
-            };
+    fn seated_player(&self, side: char) -> Option<&String> {
+        self.inner.seats.get(&side)
+    }
 
-            println!("🔍 비밀번호 필터링: 방={}, 비밀번호={:?}, 필터={:?}, 매치={}", 
-                     room.id, inner.password, has_password_filter, password_match);
+    fn join(
+        &mut self,
+        tx: MemberTx,
+        invited: bool,
+        verified: Option<Arc<RoomPassword>>,
+        player_name: String,
+        player_id: String,
+    ) -> Result<Subscription, ServerError> {
+        let inner = &mut self.inner;
+
+        if inner.banned.contains(&player_id) {
+            return Err(ServerError::BannedFromRoom);
+        }
 
-            if !password_match {
-                println!("❌ 비밀번호 필터로 인해 제외됨: {}", room.id);
-                continue;
-            }
+        if inner.visibility == Visibility::Private && !invited {
+            return Err(ServerError::InviteCodeRequired);
+        }
 
-            // 최대 플레이어 수 필터링
-            let max_players_match = match max_players_filter {
-                Some(serde_json::Value::Number(max_p)) => {
-                    if let Some(max_u64) = max_p.as_u64() {
-                        inner.max_players == max_u64 as usize
-                    } else {
-                        true
-                    }
-                }
-                _ => true, // null이거나 다른 값이면 모두 포함
-            };
+        // 비밀번호 검증 (해시 비교는 check_password에서 끝남)
+        if !password_verified(&inner.password, &verified) {
+            return Err(ServerError::InvalidPassword);
+        }
 
-            if !max_players_match {
-                continue;
-            }
+        // 방이 가득 찼는지 확인
+        if inner.players.len() >= inner.max_players {
+            return Err(ServerError::RoomFull);
+        }
 
-            // 방 정보 수집
-            let room_info = json!({
-                "id": room.id.clone(), // 실제 방 ID 사용
-                "name": inner.name.clone(),
-                "status": match inner.status {
-                    RoomStatus::Waiting => "waiting",
-                    RoomStatus::Playing => "playing",
-                    RoomStatus::Finished => "finished",
-                },
-                "owner": inner.owner.clone(),
-                "currentPlayers": inner.players.len(),
-                "maxPlayers": inner.max_players,
-                "hasPassword": inner.password.is_some(),
-                "createdAt": inner.last_activity
-            });
+        // 이미 참가한 플레이어인지 확인
+        if inner.players.contains_key(&player_id) {
+            return Err(ServerError::AlreadyJoined);
+        }
 
-            rooms.push(room_info);
+        // 관전 중인 경우 먼저 관전을 종료해야 함
+        if inner.spectators.contains_key(&player_id) {
+            return Err(ServerError::AlreadySpectating);
         }
-    }
 
-    ServerMsg::RoomList {
-        rooms: rooms.clone(),
-        total_count: rooms.len(),
-        filters: filters.clone(),
+        // 빈 좌석 찾기
+        let Some(side) = ['W', 'B']
+            .into_iter()
+            .find(|k| !inner.seats.contains_key(k))
+        else {
+            // 좌석이 모두 찬 경우 (이론적으로는 발생하지 않아야 함)
+            return Err(ServerError::NoAvailableSeats);
+        };
+
+        // 플레이어의 고유한 표시명 생성
+        let unique_display_name = crate::types::generate_unique_display_name(inner, &player_name);
+
+        // 플레이어 등록 & 좌석 배정
+        inner.players.insert(
+            player_id.clone(),
+            Player {
+                id: player_id.clone(),
+                name: unique_display_name,
+                tx: tx.clone(),
+                joined_at: ts(),
+                session_token: new_session_token(),
+                resume_deadline: None,
+            },
+        );
+        inner.ready.insert(player_id.clone(), false);
+        inner.seats.insert(side, player_id.clone());
+
+        self.send_joined(&tx, &player_id);
+        Ok(self.tx.subscribe())
+    }
+
+    fn resume(
+        &mut self,
+        tx: MemberTx,
+        player_id: &str,
+        session_token: &Secret,
+    ) -> Result<(String, Subscription), ServerError> {
+        let player = self
+            .inner
+            .players
+            .get_mut(player_id)
+            .filter(|p| session_token.matches(&p.session_token))
+            .ok_or(ServerError::InvalidSession)?;
+        player.tx = tx.clone();
+        player.resume_deadline = None;
+        let player_name = player.name.clone();
+        info!("🔁 세션 재접속: 방={}, 플레이어={}", self.id, player_id);
+
+        let inner = &self.inner;
+        let game_state = (inner.status != RoomStatus::Waiting)
+            .then(|| serde_json::to_value(inner.game.snapshot()).unwrap());
+        send_to(
+            &tx,
+            ServerMsg::SessionResumed {
+                room_id: self.id.clone(),
+                room_name: inner.name.clone(),
+                player_id: player_id.to_string(),
+                status: inner.status,
+                players: crate::types::collect_players(inner),
+                spectators: crate::types::collect_spectators(inner),
+                game_id: inner.game_id.clone(),
+                game_state,
+                series: inner.series.clone(),
+            },
+        );
+        self.broadcast(ServerMsg::PlayerStatus {
+            room_id: self.id.clone(),
+            player_id: player_id.to_string(),
+            status: "reconnected".into(),
+            last_seen: ts(),
+            message: None,
+        });
+        self.broadcast_members();
+        Ok((player_name, self.tx.subscribe()))
+    }
+
+    /// 재시작 후 돌아오지 않은 플레이어 중 가장 이른 유예 마감 시각
+    fn next_resume_deadline(&self) -> Option<u64> {
+        self.inner
+            .players
+            .values()
+            .filter_map(|p| p.resume_deadline)
+            .min()
+    }
+
+    /// 유예 시간이 지나도록 돌아오지 않은 플레이어를 자리에서 뺍니다.
+    fn expire_sessions(&mut self) {
+        let now = ts();
+        let expired: Vec<String> = self
+            .inner
+            .players
+            .values()
+            .filter(|p| p.resume_deadline.is_some_and(|d| d <= now))
+            .map(|p| p.id.clone())
+            .collect();
+        for player_id in expired {
+            info!(
+                "⌛ 재접속 유예 시간 초과: 방={}, 플레이어={}",
+                self.id, player_id
+            );
+            self.leave(player_id, "session_expired");
+            if self.closed {
+                return;
+            }
+        }
+    }
+
+    fn snapshot(&self) -> RoomSnapshot {
+        let inner = &self.inner;
+        RoomSnapshot {
+            id: self.id.clone(),
+            name: inner.name.clone(),
+            status: inner.status,
+            owner: inner.owner.clone(),
+            password_hash: inner.password.as_ref().map(|p| p.phc().to_string()),
+            max_players: inner.max_players,
+            players: inner
+                .players
+                .values()
+                .map(|p| PlayerSnapshot {
+                    id: p.id.clone(),
+                    name: p.name.clone(),
+                    joined_at: p.joined_at,
+                    session_token: p.session_token.clone(),
+                })
+                .collect(),
+            spectator_policy: inner.spectator_policy,
+            chat_policy: inner.chat_policy,
+            variant: inner.variant,
+            clock: inner.clock,
+            start_policy: inner.start_policy,
+            series: inner.series.clone(),
+            rematch_offer: inner.rematch_offer.clone(),
+            visibility: inner.visibility,
+            invite_code: inner.invite_code.clone(),
+            seats: inner.seats.clone(),
+            swap_request: inner.swap_request.clone(),
+            banned: inner.banned.clone(),
+            ready: inner.ready.clone(),
+            game: inner.game.clone(),
+            game_id: inner.game_id.clone(),
+            game_started_at: inner.game_started_at,
+            stats: inner.stats.clone(),
+            moves: inner.moves.clone(),
+            last_game: inner.last_game.clone(),
+            created_at: inner.created_at,
+        }
+    }
+
+    /// 플레이어를 좌석에서 빼고 남은 구성원에게 `status`(left_room/kicked/banned)로 알립니다.
+    fn leave(&mut self, player_id: String, status: &str) {
+        let inner = &mut self.inner;
+        if inner.players.remove(&player_id).is_none() {
+            return;
+        }
+        inner.ready.remove(&player_id);
+
+        // 좌석에서 제거 (진영 교체 요청도 취소)
+        inner.seats.retain(|_, seated| *seated != player_id);
+        inner.swap_request = None;
+        inner.rematch_offer = None;
+
+        // 방장이 나간 경우 새로운 방장 지정
+        if inner.owner == player_id {
+            if let Some(new_owner_id) = self.next_owner() {
+                self.change_owner(new_owner_id, "owner_left");
+            }
+        }
+
+        // 게임 중이었다면 게임 종료 처리 및 게임 정보 초기화
+        if self.inner.status == RoomStatus::Playing {
+            let inner = &mut self.inner;
+            inner.status = RoomStatus::Waiting;
+
+            // 게임 정보 초기화
+            inner.game = GameState::new();
+            inner.game_id = Uuid::new_v4().to_string();
+
+            // 모든 플레이어의 준비 상태 초기화
+            for ready in inner.ready.values_mut() {
+                *ready = false;
+            }
+
+            self.broadcast(ServerMsg::PlayerStatus {
+                room_id: self.id.clone(),
+                player_id: "system".to_string(),
+                status: "game_cancelled".into(),
+                last_seen: ts(),
+                message: None,
+            });
+        }
+
+        // 남은 플레이어들에게 플레이어 나감 알림
+        self.broadcast(ServerMsg::PlayerStatus {
+            room_id: self.id.clone(),
+            player_id,
+            status: status.into(),
+            last_seen: ts(),
+            message: None,
+        });
+
+        // 방 정보 업데이트 알림
+        self.broadcast_members();
+
+        // 플레이어가 모두 나간 방은 즉시 삭제 (남아있는 관전자에게는 방 삭제를 알림)
+        if self.inner.players.is_empty() {
+            self.close();
+        }
+    }
+
+    fn spectate(
+        &mut self,
+        tx: MemberTx,
+        invited: bool,
+        verified: Option<Arc<RoomPassword>>,
+        spectator_name: String,
+        spectator_id: String,
+    ) -> Result<Subscription, ServerError> {
+        let inner = &mut self.inner;
+
+        if inner.banned.contains(&spectator_id) {
+            return Err(ServerError::BannedFromRoom);
+        }
+
+        if inner.visibility == Visibility::Private && !invited {
+            return Err(ServerError::InviteCodeRequired);
+        }
+
+        // 비밀번호 검증 (관전자도 동일하게 적용)
+        if !password_verified(&inner.password, &verified) {
+            return Err(ServerError::InvalidPassword);
+        }
+
+        // 관전 정책 확인
+        if !inner.spectator_policy.allow {
+            return Err(ServerError::SpectatingDisabled);
+        }
+        if inner.spectators.len() >= inner.spectator_policy.max {
+            return Err(ServerError::SpectatorsFull);
+        }
+
+        if inner.players.contains_key(&spectator_id) {
+            return Err(ServerError::AlreadyJoined);
+        }
+        if inner.spectators.contains_key(&spectator_id) {
+            return Err(ServerError::AlreadySpectating);
+        }
+
+        let unique_display_name =
+            crate::types::generate_unique_display_name(inner, &spectator_name);
+        inner.spectators.insert(
+            spectator_id.clone(),
+            Spectator {
+                id: spectator_id.clone(),
+                name: unique_display_name.clone(),
+                tx: tx.clone(),
+            },
+        );
+
+        // 관전자에게 현재 방/게임 상태 전송 (게임 중이면 보드 포함)
+        // 중계 지연 중에는 실시간 보드를 숨기고, 지연된 PIECE_MOVED의 gameState로 따라잡게 함
+        let game_state = if inner.status == RoomStatus::Waiting
+            || inner.spectator_policy.delay != SpectatorDelay::Off
+        {
+            None
+        } else {
+            Some(serde_json::to_value(inner.game.snapshot()).unwrap())
+        };
+        let msg = ServerMsg::SpectateJoined {
+            room_id: self.id.clone(),
+            room_name: inner.name.clone(),
+            spectator_id,
+            spectator_name: unique_display_name,
+            status: inner.status.as_str().to_string(),
+            players: crate::types::collect_players(inner),
+            spectators: crate::types::collect_spectators(inner),
+            game_id: inner.game_id.clone(),
+            game_state,
+        };
+        send_to(&tx, msg);
+
+        // 방 구성원들에게 관전자 목록 갱신 알림
+        self.broadcast_members();
+        Ok(self.spectator_tx.subscribe())
+    }
+
+    fn leave_spectator(&mut self, spectator_id: &str) {
+        if self.inner.spectators.remove(spectator_id).is_some() {
+            self.broadcast_members();
+        }
+    }
+
+    /// 방장이 나갔을 때 이어받을 플레이어.
+    /// 좌석에 앉은 플레이어가 먼저이고, 그 안에서는 가장 오래 있었던 순서 (같으면 ID 순)입니다.
+    /// 관전자는 승계하지 않습니다 (플레이어가 모두 나가면 방이 삭제됨).
+    fn next_owner(&self) -> Option<String> {
+        let inner = &self.inner;
+        inner
+            .players
+            .values()
+            .min_by_key(|p| {
+                let seated = inner.seats.values().any(|id| *id == p.id);
+                (!seated, p.joined_at, &p.id)
+            })
+            .map(|p| p.id.clone())
+    }
+
+    /// 방장을 바꾸고 `OWNER_CHANGED`로 알립니다. `reason`: transferred / owner_left
+    fn change_owner(&mut self, new_owner_id: String, reason: &str) {
+        let previous = std::mem::replace(&mut self.inner.owner, new_owner_id.clone());
+        let owner_name = self
+            .inner
+            .players
+            .get(&new_owner_id)
+            .map(|p| p.name.clone())
+            .unwrap_or_default();
+
+        info!(
+            "👑 방장 변경: 방={}, {} -> {} ({})",
+            self.id, previous, new_owner_id, reason
+        );
+        self.broadcast(ServerMsg::OwnerChanged {
+            room_id: self.id.clone(),
+            owner_id: new_owner_id,
+            owner_name,
+            previous_owner_id: previous,
+            reason: reason.into(),
+        });
+    }
+
+    fn transfer_ownership(
+        &mut self,
+        player_id: &str,
+        target_id: String,
+    ) -> Result<(), ServerError> {
+        if self.inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+        if target_id == player_id {
+            return Ok(());
+        }
+        if !self.inner.players.contains_key(&target_id) {
+            // 관전자에게는 넘길 수 없음
+            return Err(if self.inner.spectators.contains_key(&target_id) {
+                ServerError::NotAPlayer {
+                    member_id: target_id,
+                }
+            } else {
+                ServerError::MemberNotFound {
+                    member_id: target_id,
+                }
+            });
+        }
+
+        self.change_owner(target_id, "transferred");
+        // 플레이어 목록의 isOwner 갱신
+        self.broadcast_members();
+        Ok(())
+    }
+
+    /// 내보낸 구성원의 연결은 `kicked`/`banned` 알림을 받은 뒤 방 구독을 닫습니다.
+    fn kick(&mut self, player_id: &str, target_id: String, ban: bool) -> Result<(), ServerError> {
+        if self.inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+        if target_id == player_id {
+            return Err(ServerError::CannotKickSelf);
+        }
+
+        let status = if ban { "banned" } else { "kicked" };
+        // 차단은 재접속(RESUME_SESSION)에 쓰는 플레이어 ID에 걸고 스냅샷에도 남김
+        if self.inner.players.contains_key(&target_id) {
+            if ban {
+                self.inner.banned.insert(target_id.clone());
+            }
+            self.leave(target_id.clone(), status);
+        } else if self.inner.spectators.contains_key(&target_id) {
+            if ban {
+                self.inner.banned.insert(target_id.clone());
+            }
+            // 관전자 본인도 받을 수 있도록 목록에서 빼기 전에 알림
+            self.broadcast(ServerMsg::PlayerStatus {
+                room_id: self.id.clone(),
+                player_id: target_id.clone(),
+                status: status.into(),
+                last_seen: ts(),
+                message: None,
+            });
+            self.leave_spectator(&target_id);
+        } else {
+            return Err(ServerError::MemberNotFound {
+                member_id: target_id,
+            });
+        }
+
+        info!(
+            "🚫 구성원 내보냄: 방={}, 대상={}, 차단={}",
+            self.id, target_id, ban
+        );
+        Ok(())
+    }
+
+    fn set_spectator_policy(
+        &mut self,
+        player_id: &str,
+        allow: Option<bool>,
+        max: Option<usize>,
+        delay: Option<SpectatorDelay>,
+    ) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+
+        let current = inner.spectator_policy;
+        let policy = SpectatorPolicy {
+            allow: allow.unwrap_or(current.allow),
+            max: max.unwrap_or(current.max),
+            delay: delay.unwrap_or(current.delay),
+        };
+
+        // 게임 중 지연 설정을 바꾸면 대기 중인 이벤트가 한꺼번에 노출될 수 있으므로 금지
+        if inner.status == RoomStatus::Playing && current.delay != policy.delay {
+            return Err(ServerError::GameInProgress);
+        }
+
+        // 이미 입장한 관전자는 유지하고, 이후 입장부터 새 정책을 적용
+        inner.spectator_policy = policy;
+        self.delay_tx.send_replace(policy.delay);
+        self.broadcast(ServerMsg::SpectatorPolicyUpdated {
+            room_id: self.id.clone(),
+            allow_spectators: policy.allow,
+            max_spectators: policy.max,
+            spectator_delay: policy.delay.to_json(),
+        });
+
+        Ok(())
+    }
+
+    fn settings(&self) -> RoomSettings {
+        let inner = &self.inner;
+        RoomSettings {
+            room_name: inner.name.clone(),
+            has_password: inner.password.is_some(),
+            visibility: inner.visibility,
+            variant: inner.variant,
+            clock: inner.clock,
+            start_policy: inner.start_policy,
+            best_of: inner.series.best_of,
+            allow_spectators: inner.spectator_policy.allow,
+            max_spectators: inner.spectator_policy.max,
+            spectator_delay: inner.spectator_policy.delay.to_json(),
+            players_see_spectator_chat: inner.chat_policy.players_see_spectator_chat,
+        }
+    }
+
+    fn update_settings(
+        &mut self,
+        player_id: String,
+        update: RoomSettingsUpdate,
+        password: Option<Option<Arc<RoomPassword>>>,
+    ) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+
+        let name = match update.room_name.as_deref().map(str::trim) {
+            Some("") => {
+                return Err(ServerError::InvalidMessage {
+                    msg_type: "UPDATE_ROOM_SETTINGS".into(),
+                    field: "roomName".into(),
+                    reason: "room name must not be empty".into(),
+                })
+            }
+            Some(name) => name.to_string(),
+            None => inner.name.clone(),
+        };
+        let variant = update.variant.unwrap_or(inner.variant);
+        let clock = update.clock.unwrap_or(inner.clock);
+        let start_policy = update.start_policy.unwrap_or(inner.start_policy);
+        let best_of = match update.best_of {
+            Some(n) if n % 2 == 0 || n > MatchSeries::MAX_BEST_OF => {
+                return Err(ServerError::InvalidMessage {
+                    msg_type: "UPDATE_ROOM_SETTINGS".into(),
+                    field: "bestOf".into(),
+                    reason: format!(
+                        "must be an odd number from 1 to {}",
+                        MatchSeries::MAX_BEST_OF
+                    ),
+                })
+            }
+            Some(n) => n,
+            None => inner.series.best_of,
+        };
+        let current = inner.spectator_policy;
+        let spectator_policy = SpectatorPolicy {
+            allow: update.allow_spectators.unwrap_or(current.allow),
+            max: update.max_spectators.unwrap_or(current.max),
+            delay: update.spectator_delay.unwrap_or(current.delay),
+        };
+
+        // 규칙·시간·선공 방식·관전 지연은 게임 중에 바꿀 수 없음 (이름, 비밀번호 등은 가능)
+        if inner.status == RoomStatus::Playing
+            && (variant != inner.variant
+                || clock != inner.clock
+                || start_policy != inner.start_policy
+                || best_of != inner.series.best_of
+                || spectator_policy.delay != current.delay)
+        {
+            return Err(ServerError::GameInProgress);
+        }
+
+        inner.name = name;
+        inner.variant = variant;
+        inner.clock = clock;
+        inner.start_policy = start_policy;
+        if best_of != inner.series.best_of {
+            // 판 수가 바뀌면 시리즈를 처음부터
+            inner.series = MatchSeries::new(best_of);
+        }
+        if let Some(password) = password {
+            inner.password = password;
+        }
+        if let Some(visibility) = update.visibility {
+            inner.visibility = visibility;
+        }
+        inner.spectator_policy = spectator_policy;
+        if let Some(see) = update.players_see_spectator_chat {
+            inner.chat_policy.players_see_spectator_chat = see;
+        }
+        self.delay_tx.send_replace(spectator_policy.delay);
+
+        info!("⚙️ 방 설정 변경: 방={}, 방장={}", self.id, player_id);
+        self.broadcast(ServerMsg::RoomUpdated {
+            room: self.summary(),
+            settings: Some(self.settings()),
+            updated_by: Some(player_id),
+        });
+
+        Ok(())
+    }
+
+    fn regenerate_invite_code(&mut self, player_id: &str) -> Result<String, ServerError> {
+        if self.inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+        let code = self.lobby.issue_invite_code(&self.id);
+        let old = std::mem::replace(&mut self.inner.invite_code, code.clone());
+        self.lobby.revoke_invite_code(&old);
+        info!("🔑 초대 코드 재발급: 방={}", self.id);
+        Ok(code)
+    }
+
+    fn set_ready(&mut self, player_id: String, is_ready: bool) {
+        let inner = &mut self.inner;
+        inner.ready.insert(player_id.clone(), is_ready);
+        let all_ready = inner.players.len() == 2
+            && inner
+                .players
+                .keys()
+                .all(|pid| inner.ready.get(pid).copied().unwrap_or(false));
+        self.broadcast(ServerMsg::PlayerReady {
+            room_id: self.id.clone(),
+            player_id,
+            is_ready,
+            all_ready,
+        });
+    }
+
+    fn broadcast_seats(&self, reason: &str) {
+        let seats = self
+            .inner
+            .seats
+            .iter()
+            .map(|(side, id)| (side.to_string(), id.clone()))
+            .collect();
+        self.broadcast(ServerMsg::SeatsChanged {
+            room_id: self.id.clone(),
+            seats,
+            reason: reason.into(),
+        });
+        // 플레이어 목록의 side 갱신
+        self.broadcast_members();
+    }
+
+    fn choose_seat(&mut self, player_id: &str, side: char) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if inner.status == RoomStatus::Playing {
+            return Err(ServerError::GameInProgress);
+        }
+        if !inner.players.contains_key(player_id) {
+            return Err(ServerError::NotInRoom);
+        }
+        match inner.seats.get(&side) {
+            Some(seated) if seated == player_id => return Ok(()),
+            Some(_) => return Err(ServerError::SeatTaken { side }),
+            None => {}
+        }
+
+        inner.seats.retain(|_, seated| seated != player_id);
+        inner.seats.insert(side, player_id.to_string());
+        inner.swap_request = None;
+        info!(
+            "💺 좌석 변경: 방={}, 플레이어={}, 진영={}",
+            self.id, player_id, side
+        );
+        self.broadcast_seats("chosen");
+        Ok(())
+    }
+
+    fn swap_sides(&mut self, player_id: String, accept: bool) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if inner.status == RoomStatus::Playing {
+            return Err(ServerError::GameInProgress);
+        }
+        if !inner.seats.values().any(|seated| *seated == player_id) {
+            return Err(ServerError::NotInRoom);
+        }
+        if inner.seats.len() != 2 {
+            return Err(ServerError::NeedTwoPlayers);
+        }
+
+        if !accept {
+            if inner.swap_request.take().is_some() {
+                self.broadcast(ServerMsg::SwapSidesDeclined {
+                    room_id: self.id.clone(),
+                    declined_by: player_id,
+                });
+            }
+            return Ok(());
+        }
+
+        match inner.swap_request.take() {
+            // 상대가 먼저 요청했으면 동의로 보고 교체
+            Some(requested_by) if requested_by != player_id => {
+                let w = inner.seats.insert('W', inner.seats[&'B'].clone());
+                inner.seats.insert('B', w.expect("두 좌석이 모두 참"));
+                info!(
+                    "🔁 진영 교체: 방={}, {} <-> {}",
+                    self.id, requested_by, player_id
+                );
+                self.broadcast_seats("swapped");
+            }
+            // 이미 내가 요청해 둔 상태
+            Some(requested_by) => inner.swap_request = Some(requested_by),
+            None => {
+                inner.swap_request = Some(player_id.clone());
+                self.broadcast(ServerMsg::SwapSidesRequested {
+                    room_id: self.id.clone(),
+                    requested_by: player_id,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn start_game(&mut self, player_id: &str) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+
+        if inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+
+        if inner.seats.len() != 2 {
+            return Err(ServerError::NeedTwoPlayers);
+        }
+
+        // 모든 플레이어가 준비되었는지 확인
+        let all_ready = inner
+            .players
+            .keys()
+            .all(|pid| inner.ready.get(pid).copied().unwrap_or(false));
+
+        if !all_ready {
+            return Err(ServerError::PlayersNotReady);
+        }
+
+        if !self.drain.is_accepting() {
+            return Err(ServerError::ServerDraining);
+        }
+
+        inner.status = RoomStatus::Playing;
+        inner.game_id = Uuid::new_v4().to_string();
+        let opening_throws = self.new_game(false);
+        self.inner.series.begin_game(self.inner.seats.values());
+        self.broadcast_game_started(opening_throws);
+
+        Ok(())
+    }
+
+    fn offer_rematch(&mut self, player_id: String) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if !inner.seats.values().any(|seated| *seated == player_id) {
+            return Err(ServerError::NotInRoom);
+        }
+        if inner.status != RoomStatus::Finished {
+            return Err(ServerError::GameNotFinished);
+        }
+        if inner.seats.len() != 2 {
+            return Err(ServerError::NeedTwoPlayers);
+        }
+        if !self.drain.is_accepting() {
+            return Err(ServerError::ServerDraining);
+        }
+        // 상대가 이미 제안했으면 수락으로 처리
+        if inner
+            .rematch_offer
+            .as_ref()
+            .is_some_and(|id| *id != player_id)
+        {
+            return self.accept_rematch(&player_id);
+        }
+        if inner.rematch_offer.is_none() {
+            inner.rematch_offer = Some(player_id.clone());
+            self.broadcast(ServerMsg::RematchOffered {
+                room_id: self.id.clone(),
+                offered_by: player_id,
+            });
+        }
+        Ok(())
+    }
+
+    /// 상대의 재대결 제안을 받아 진영을 바꿔 바로 다음 판을 시작합니다.
+    fn accept_rematch(&mut self, player_id: &str) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if !inner.seats.values().any(|seated| seated == player_id) {
+            return Err(ServerError::NotInRoom);
+        }
+        if inner.status != RoomStatus::Finished {
+            return Err(ServerError::GameNotFinished);
+        }
+        if inner.seats.len() != 2 {
+            return Err(ServerError::NeedTwoPlayers);
+        }
+        match &inner.rematch_offer {
+            Some(offered_by) if offered_by != player_id => {}
+            _ => return Err(ServerError::NoRematchOffer),
+        }
+        if !self.drain.is_accepting() {
+            return Err(ServerError::ServerDraining);
+        }
+
+        inner.status = RoomStatus::Playing;
+        inner.game_id = Uuid::new_v4().to_string();
+        info!("🔁 재대결 시작: 방={}", self.id);
+        let opening_throws = self.new_game(true);
+        self.inner.series.begin_game(self.inner.seats.values());
+        self.broadcast_game_started(opening_throws);
+        Ok(())
+    }
+
+    /// 새 게임판을 만들고 방의 선공 방식에 따라 진영/선공을 정합니다.
+    /// 재대결이면 무작위 대신 지난 판과 진영을 바꿉니다.
+    /// 시작 굴림으로 선공을 정했으면 그 기록을 돌려줍니다.
+    fn new_game(&mut self, rematch: bool) -> Option<Vec<OpeningThrow>> {
+        let inner = &mut self.inner;
+        inner.game = GameState::new();
+        inner.swap_request = None;
+        inner.rematch_offer = None;
+
+        let swap = match inner.start_policy {
+            _ if rematch => true,
+            StartPolicy::RandomSides => rand::random::<bool>(),
+            StartPolicy::Fixed | StartPolicy::OpeningThrow => false,
+        };
+        if swap {
+            let w = inner.seats.remove(&'W');
+            let b = inner.seats.remove(&'B');
+            if let Some(b) = b {
+                inner.seats.insert('W', b);
+            }
+            if let Some(w) = w {
+                inner.seats.insert('B', w);
+            }
+        }
+
+        let opening_throws = match inner.start_policy {
+            StartPolicy::Fixed | StartPolicy::RandomSides => None,
+            StartPolicy::OpeningThrow => {
+                let (first, throws) = game::opening_throw(&mut inner.game.dice);
+                inner.game.turn = first;
+                Some(throws)
+            }
+        };
+
+        inner.game_started_at = ts();
+        inner.stats = GameStats::new(inner.game.turn);
+        inner.moves.clear();
+        opening_throws
+    }
+
+    fn broadcast_game_started(&self, opening_throws: Option<Vec<OpeningThrow>>) {
+        let inner = &self.inner;
+        self.broadcast(ServerMsg::GameStarted {
+            room_id: self.id.clone(),
+            game_id: inner.game_id.clone(),
+            players: crate::types::collect_players(inner),
+            initial_turn: inner.game.turn.to_string(),
+            game_state: serde_json::to_value(inner.game.snapshot()).unwrap(),
+            start_policy: inner.start_policy,
+            opening_throws,
+            series: inner.series.clone(),
+        });
+    }
+
+    fn reset_game(&mut self, player_id: String) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+
+        if inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+
+        // 게임이 진행 중이거나 완료된 상태에서만 리셋 가능
+        if inner.status != RoomStatus::Playing && inner.status != RoomStatus::Finished {
+            return Err(ServerError::GameNotInProgress);
+        }
+        // 끝난 게임을 리셋하면 새 판이 시작되므로 드레인 중에는 막음
+        if inner.status == RoomStatus::Finished && !self.drain.is_accepting() {
+            return Err(ServerError::ServerDraining);
+        }
+
+        let old = std::mem::replace(&mut inner.game_id, Uuid::new_v4().to_string());
+        // 끝난 게임을 리셋하면 시리즈의 다음 판, 진행 중인 게임을 리셋하면 같은 판을 다시 시작
+        let finished = inner.status == RoomStatus::Finished;
+        inner.status = RoomStatus::Playing;
+        let opening_throws = self.new_game(false);
+        if finished {
+            self.inner.series.begin_game(self.inner.seats.values());
+        }
+
+        self.broadcast(ServerMsg::GameReset {
+            room_id: self.id.clone(),
+            game_id: old,
+            reset_by: player_id,
+            new_game_id: self.inner.game_id.clone(),
+        });
+        self.broadcast_game_started(opening_throws);
+
+        Ok(())
+    }
+
+    fn switch_turn(&mut self) {
+        let game = &mut self.inner.game;
+        game.turn = if game.turn == 'W' { 'B' } else { 'W' };
+        game.last_roll = None; // 롤 값 초기화
+        self.inner.stats.begin_turn(game.turn);
+    }
+
+    fn roll_sticks(&mut self, player_id: String) -> Result<(), ServerError> {
+        if self.inner.status != RoomStatus::Playing {
+            warn!("❌ 게임이 시작되지 않음: 상태={:?}", self.inner.status);
+            return Err(ServerError::GameNotStarted);
+        }
+
+        // 턴 체크
+        let turn = self.inner.game.turn;
+        if self.seated_player(turn) != Some(&player_id) {
+            warn!(
+                "❌ 턴이 아님: 현재 턴 플레이어={}, 요청 플레이어={}",
+                self.seated_player(turn)
+                    .map(|s| s.as_str())
+                    .unwrap_or("없음"),
+                player_id
+            );
+            return Err(ServerError::NotYourTurn);
+        }
+
+        let (roll, faces, _grants, can_move) = self.inner.game.roll();
+        self.inner.stats.rolled(turn, roll, can_move);
+        self.rolls.record(roll);
+        info!(
+            "🎲 주사위 굴림 결과: roll={}, faces={:?}, can_move={}",
+            roll, faces, can_move
+        );
+
+        let receiver_count = self.broadcast(ServerMsg::SticksRolled {
+            room_id: self.id.clone(),
+            game_id: self.inner.game_id.clone(),
+            player_id: player_id.clone(),
+            roll,
+            faces,
+            turn: turn.to_string(),
+            can_move,
+        });
+        debug!(
+            "✅ STICKS_ROLLED 브로드캐스트, 수신자 수: {}",
+            receiver_count
+        );
+
+        // 이동할 수 없는 경우 자동으로 턴 패스
+        if !can_move && !self.inner.game.game_over {
+            info!(
+                "🚫 이동 불가능 - 자동 턴 패스: 방={}, 플레이어={}",
+                self.id, player_id
+            );
+            self.switch_turn();
+            self.broadcast(ServerMsg::TurnChanged {
+                room_id: self.id.clone(),
+                game_id: self.inner.game_id.clone(),
+                new_turn: self.inner.game.turn.to_string(),
+                reason: "no_legal_moves".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn move_piece(&mut self, player_id: String, mv: MoveRequest) -> Result<(), ServerError> {
+        if self.inner.status != RoomStatus::Playing {
+            return Err(ServerError::GameNotStarted);
+        }
+        let side = mv.side;
+        // 좌석-플레이어 검증
+        if self.seated_player(side) != Some(&player_id) {
+            return Err(ServerError::NotYourSide);
+        }
+        if self.inner.game.turn != side {
+            return Err(ServerError::NotYourTurn);
+        }
+        let MoveRequest {
+            piece_index: idx,
+            from,
+            to,
+            roll,
+            ..
+        } = mv;
+
+        let (ok, extra, _passed_water, captured) =
+            self.inner.game.apply_move(side, idx, from, to, roll);
+        if !ok {
+            return Err(ServerError::InvalidMove);
+        }
+        self.inner
+            .stats
+            .moved(side, captured.is_some(), to == WATER_SQUARE, extra);
+        self.inner.moves.push(MoveRecord::Move {
+            side,
+            piece_index: idx,
+            from,
+            to,
+            roll,
+            captured: captured.is_some(),
+            extra_turn: extra,
+            at: ts(),
+        });
+
+        // 업데이트 및 브로드캐스트
+        let move_payload = json!({
+            "playerId": player_id,
+            "side": side.to_string(),
+            "pieceIndex": idx,
+            "from": from,
+            "to": to,
+            "roll": roll,
+            "captured": captured.map(|(c, _)| c.to_string()),
+            "extraTurn": extra
+        });
+        let gs = self.inner.game.snapshot();
+
+        info!(
+            "🔄 말 이동 브로드캐스트: 방={}, 플레이어={}, 이동={:?}",
+            self.id, player_id, move_payload
+        );
+
+        let receiver_count = self.broadcast(ServerMsg::PieceMoved {
+            room_id: self.id.clone(),
+            game_id: self.inner.game_id.clone(),
+            move_: move_payload,
+            game_state: serde_json::to_value(&gs).unwrap(),
+        });
+        debug!("✅ PIECE_MOVED 브로드캐스트, 수신자 수: {}", receiver_count);
+
+        // 턴 전환
+        if !extra {
+            self.switch_turn();
+            self.broadcast(ServerMsg::TurnChanged {
+                room_id: self.id.clone(),
+                game_id: self.inner.game_id.clone(),
+                new_turn: self.inner.game.turn.to_string(),
+                reason: "normal_move".to_string(),
+            });
+        }
+
+        if self.inner.game.game_over {
+            let winner_name = self
+                .seated_player(side)
+                .and_then(|pid| self.inner.players.get(pid))
+                .map(|p| p.name.clone())
+                .unwrap_or_else(|| "Unknown".into());
+            self.inner.series.record_win(&player_id);
+            let record = self.game_record(side, &player_id);
+
+            // 게임 종료 메시지 전송
+            self.broadcast(ServerMsg::GameEnded {
+                room_id: self.id.clone(),
+                game_id: self.inner.game_id.clone(),
+                winner: side.to_string(),
+                winner_name: winner_name.clone(),
+                final_state: serde_json::to_value(gs).unwrap(),
+                started_at: record.started_at,
+                ended_at: record.ended_at,
+                game_duration: record.duration_ms,
+                stats: record.stats.clone(),
+                series: record.series.clone(),
+            });
+            self.ratings.record(&record);
+            store::save_in_background(self.games.clone(), record.clone());
+            self.inner.last_game = Some(record);
+
+            // 시리즈 승부가 났으면 알림 (다음 판은 새 시리즈)
+            if self.inner.series.winner() == Some(&player_id) {
+                info!(
+                    "🏆 매치 종료: 방={}, 승자={}, 전적={:?}",
+                    self.id, player_id, self.inner.series.wins
+                );
+                self.broadcast(ServerMsg::MatchEnded {
+                    room_id: self.id.clone(),
+                    winner: player_id.clone(),
+                    winner_name,
+                    series: self.inner.series.clone(),
+                });
+            }
+
+            // 방 상태를 Finished로 변경
+            self.inner.status = RoomStatus::Finished;
+
+            // 게임 종료 후 방 상태 변경 알림
+            self.broadcast(ServerMsg::PlayerStatus {
+                room_id: self.id.clone(),
+                player_id: "system".to_string(),
+                status: "game_finished".into(),
+                last_seen: ts(),
+                message: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// 방금 끝난 게임의 기록
+    fn game_record(&self, winner: char, winner_id: &str) -> GameRecord {
+        let inner = &self.inner;
+        let ended_at = ts();
+        let players = inner
+            .seats
+            .iter()
+            .map(|(side, id)| RecordPlayer {
+                side: *side,
+                player_id: id.clone(),
+                player_name: inner
+                    .players
+                    .get(id)
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        GameRecord {
+            game_id: inner.game_id.clone(),
+            room_id: self.id.clone(),
+            variant: inner.variant,
+            seed: inner.game.dice.seed(),
+            players,
+            winner,
+            winner_id: winner_id.to_string(),
+            started_at: inner.game_started_at,
+            ended_at,
+            duration_ms: ended_at.saturating_sub(inner.game_started_at),
+            stats: inner.stats.clone(),
+            series: inner.series.clone(),
+            moves: inner.moves.clone(),
+        }
+    }
+
+    fn pass_turn(
+        &mut self,
+        player_id: &str,
+        requested_roll: Option<u8>,
+    ) -> Result<(), ServerError> {
+        if self.inner.status != RoomStatus::Playing {
+            return Err(ServerError::GameNotStarted);
+        }
+
+        // 턴 체크
+        if self.seated_player(self.inner.game.turn).map(|s| s.as_str()) != Some(player_id) {
+            return Err(ServerError::NotYourTurn);
+        }
+
+        // 현재 롤 값 확인
+        if self.inner.game.last_roll != requested_roll {
+            return Err(ServerError::InvalidRoll);
+        }
+
+        let side = self.inner.game.turn;
+        self.inner.stats.passed(side);
+        self.inner.moves.push(MoveRecord::Pass { side, at: ts() });
+        self.switch_turn();
+        info!(
+            "🔄 턴 패스: 방={}, 플레이어={}, 새 턴={}",
+            self.id, player_id, self.inner.game.turn
+        );
+
+        // 턴 변경 브로드캐스트
+        self.broadcast(ServerMsg::TurnChanged {
+            room_id: self.id.clone(),
+            game_id: self.inner.game_id.clone(),
+            new_turn: self.inner.game.turn.to_string(),
+            reason: "pass_turn".to_string(),
+        });
+
+        Ok(())
+    }
+
+    fn chat(
+        &mut self,
+        sender_id: String,
+        channel: ChatChannel,
+        message: String,
+        message_type: String,
+    ) -> Result<(), ServerError> {
+        let inner = &self.inner;
+
+        let (sender_name, sender_is_spectator) = if let Some(p) = inner.players.get(&sender_id) {
+            (p.name.clone(), false)
+        } else if let Some(s) = inner.spectators.get(&sender_id) {
+            (s.name.clone(), true)
+        } else {
+            return Err(ServerError::NotInRoom);
+        };
+
+        // 관전자는 플레이어 전용 채널에 쓸 수 없음
+        if sender_is_spectator && channel == ChatChannel::Players {
+            return Err(ServerError::ChannelNotAllowed {
+                channel: channel.as_str().to_string(),
+            });
+        }
+
+        // 게임 중 관전자 채팅 숨김 설정이면 관전자 발언은 플레이어에게 전달하지 않음
+        let spectator_chat_hidden =
+            inner.status == RoomStatus::Playing && !inner.chat_policy.players_see_spectator_chat;
+        let to_players = match channel {
+            ChatChannel::Players => true,
+            ChatChannel::Spectators => !spectator_chat_hidden,
+            ChatChannel::All => !(sender_is_spectator && spectator_chat_hidden),
+        };
+        let to_spectators = channel != ChatChannel::Players;
+
+        // 수신자가 여러 명이어도 직렬화는 한 번만
+        let chat_msg = Outgoing::new(ServerMsg::ChatReceived {
+            room_id: self.id.clone(),
+            player_id: sender_id.clone(),
+            player_name: sender_name,
+            message,
+            message_type,
+            channel: channel.as_str().to_string(),
+            timestamp: ts(),
+        });
+
+        // 채널 구성원에게만 개별 전송 (보낸 사람은 항상 자신의 메시지를 받음)
+        let players = inner
+            .players
+            .iter()
+            .filter(|(id, _)| to_players || **id == sender_id)
+            .map(|(_, p)| &p.tx);
+        let spectators = inner
+            .spectators
+            .iter()
+            .filter(|(id, _)| to_spectators || **id == sender_id)
+            .map(|(_, s)| &s.tx);
+        for tx in players.chain(spectators) {
+            if let Err(e) = tx.try_send(chat_msg.clone()) {
+                warn!("❌ CHAT_RECEIVED 메시지 전송 실패: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_chat_policy(
+        &mut self,
+        player_id: &str,
+        players_see_spectator_chat: Option<bool>,
+    ) -> Result<(), ServerError> {
+        let inner = &mut self.inner;
+        if inner.owner != player_id {
+            return Err(ServerError::NotRoomOwner);
+        }
+
+        let policy = ChatPolicy {
+            players_see_spectator_chat: players_see_spectator_chat
+                .unwrap_or(inner.chat_policy.players_see_spectator_chat),
+        };
+        inner.chat_policy = policy;
+        self.broadcast(ServerMsg::ChatPolicyUpdated {
+            room_id: self.id.clone(),
+            players_see_spectator_chat: policy.players_see_spectator_chat,
+        });
+
+        Ok(())
+    }
+}
+
+// ========================= 관전자 중계 =========================
+
+struct DelayedMsg {
+    msg: Arc<Outgoing>,
+    due_at: Option<Instant>, // 초 단위 지연
+    move_no: u64,            // 수 단위 지연
+}
+
+/// 방 태스크에 현재 게임 상태를 묻습니다. 방이 이미 닫혔으면 `None`.
+async fn request_game_state(cmd_tx: &mpsc::WeakSender<RoomCmd>) -> Option<ServerMsg> {
+    let cmd_tx = cmd_tx.upgrade()?;
+    let (reply, rx) = oneshot::channel();
+    cmd_tx.send(RoomCmd::GameStateSync { reply }).await.ok()?;
+    drop(cmd_tx);
+    rx.await.ok()
+}
+
+/// 방 브로드캐스트를 구독해서 관전자 채널로 전달합니다.
+/// 게임 진행 이벤트는 방의 관전 지연 설정(초/수)만큼 늦게, 나머지는 즉시 전달합니다.
+fn spawn_spectator_relay(room: &Arc<Room>, delay_rx: watch::Receiver<SpectatorDelay>) {
+    let mut brx = room.tx.subscribe();
+    let spectator_tx = room.spectator_tx.clone();
+    // 중계 태스크가 방 태스크를 붙잡지 않도록 약한 핸들만 가짐
+    let cmd_tx = room.cmd_tx.downgrade();
+    let room_id = room.id.clone();
+
+    tokio::spawn(async move {
+        let mut queue: VecDeque<DelayedMsg> = VecDeque::new();
+        let mut moves_seen: u64 = 0;
+
+        loop {
+            let next_due = queue.front().and_then(|d| d.due_at);
+            tokio::select! {
+                recv = brx.recv() => {
+                    let msg = match recv {
+                        Ok(msg) => msg,
+                        Err(RecvError::Lagged(n)) => {
+                            warn!(
+                                "⚠️ 관전 중계 지연으로 {} 개 메시지 누락 - 게임 상태 다시 전송 (방: {})",
+                                n, room_id
+                            );
+                            // 로비 구독처럼 놓친 변경 대신 현재 상태를 보냄 (다른 진행 이벤트와 같은 지연을 거침)
+                            match request_game_state(&cmd_tx).await {
+                                Some(sync) => Outgoing::new(sync),
+                                None => continue,
+                            }
+                        }
+                        Err(RecvError::Closed) => {
+                            // 방이 사라지면 숨길 게임도 없으므로 남은 이벤트를 모두 보냄
+                            for d in queue.drain(..) {
+                                spectator_tx.send(d.msg).ok();
+                            }
+                            break;
+                        }
+                    };
+
+                    if matches!(
+                        msg.msg(),
+                        ServerMsg::GameStarted { .. } | ServerMsg::GameReset { .. }
+                    ) {
+                        // 이전 게임의 대기 이벤트는 더 이상 숨길 필요가 없음
+                        for d in queue.drain(..) {
+                            spectator_tx.send(d.msg).ok();
+                        }
+                        moves_seen = 0;
+                    }
+
+                    if !msg.msg().is_game_event() {
+                        spectator_tx.send(msg).ok();
+                        continue;
+                    }
+
+                    let delay = *delay_rx.borrow();
+                    match delay {
+                        SpectatorDelay::Off => {
+                            for d in queue.drain(..) {
+                                spectator_tx.send(d.msg).ok();
+                            }
+                            spectator_tx.send(msg).ok();
+                        }
+                        SpectatorDelay::Seconds(secs) => {
+                            queue.push_back(DelayedMsg {
+                                msg,
+                                due_at: Some(Instant::now() + Duration::from_secs(secs)),
+                                move_no: moves_seen,
+                            });
+                        }
+                        SpectatorDelay::Moves(n) => {
+                            let game_ended = msg.msg().ends_game();
+                            if matches!(msg.msg(), ServerMsg::PieceMoved { .. }) {
+                                moves_seen += 1;
+                            }
+                            queue.push_back(DelayedMsg {
+                                msg,
+                                due_at: None,
+                                move_no: moves_seen,
+                            });
+                            // 게임이 끝나면 남은 수를 모두 공개
+                            while queue
+                                .front()
+                                .is_some_and(|d| game_ended || d.move_no + n <= moves_seen)
+                            {
+                                let d = queue.pop_front().unwrap();
+                                spectator_tx.send(d.msg).ok();
+                            }
+                        }
+                    }
+                }
+                _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)),
+                    if next_due.is_some() =>
+                {
+                    let now = Instant::now();
+                    while queue.front().is_some_and(|d| d.due_at.is_some_and(|t| t <= now)) {
+                        let d = queue.pop_front().unwrap();
+                        spectator_tx.send(d.msg).ok();
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 테스트용 연결 - 방 태스크가 보내는 개인 메시지를 받는 쪽
+    fn connection() -> (MemberTx, mpsc::Receiver<Arc<Outgoing>>) {
+        mpsc::channel(64)
+    }
+
+    async fn open_room(state: &AppState, owner: &str) -> Arc<Room> {
+        let (tx, _rx) = connection();
+        let (room, _) = create_room(
+            state,
+            tx,
+            "테스트 방".into(),
+            None,
+            2,
+            SpectatorPolicy::default(),
+            RuleVariant::Standard,
+            Visibility::Public,
+            owner.into(),
+            owner.into(),
+        )
+        .await
+        .unwrap();
+        room
+    }
+
+    async fn join(state: &AppState, room: &Room, player_id: &str) -> Result<(), ServerError> {
+        let (tx, _rx) = connection();
+        join_room(
+            state,
+            tx,
+            RoomRef::Id(room.id.clone()),
+            None,
+            player_id.into(),
+            player_id.into(),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn spectate(
+        state: &AppState,
+        room: &Room,
+        spectator_id: &str,
+    ) -> Result<(), ServerError> {
+        let (tx, _rx) = connection();
+        spectate_room(
+            state,
+            tx,
+            RoomRef::Id(room.id.clone()),
+            None,
+            spectator_id.into(),
+            spectator_id.into(),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn ban_holds_for_the_player_id_across_rejoins_and_restarts() {
+        let state = AppState::for_tests();
+        let room = open_room(&state, "owner").await;
+        join(&state, &room, "guest").await.unwrap();
+        spectate(&state, &room, "watcher").await.unwrap();
+
+        // 방장이 아니면 차단할 수 없음
+        let err = kick_player(&room, "guest".into(), "owner".into(), true).await;
+        assert!(matches!(err, Err(ServerError::NotRoomOwner)));
+
+        kick_player(&room, "owner".into(), "guest".into(), true)
+            .await
+            .unwrap();
+        kick_player(&room, "owner".into(), "watcher".into(), true)
+            .await
+            .unwrap();
+
+        // 새 연결로 같은 ID를 쓰면 입장도 관전도 불가
+        for id in ["guest", "watcher"] {
+            let err = join(&state, &room, id).await;
+            assert!(matches!(err, Err(ServerError::BannedFromRoom)));
+            let err = spectate(&state, &room, id).await;
+            assert!(matches!(err, Err(ServerError::BannedFromRoom)));
+        }
+
+        // 스냅샷에서 복원한 방에서도 유지
+        let snap = snapshot_room(&room).await.unwrap();
+        let restored = AppState::for_tests();
+        restore_room(&restored, snap, Duration::from_secs(60));
+        let room = restored.rooms.get(&room.id).unwrap().clone();
+        let err = join(&restored, &room, "guest").await;
+        assert!(matches!(err, Err(ServerError::BannedFromRoom)));
+        join(&restored, &room, "someone-else").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn kick_without_ban_allows_rejoin() {
+        let state = AppState::for_tests();
+        let room = open_room(&state, "owner").await;
+        join(&state, &room, "guest").await.unwrap();
+
+        kick_player(&room, "owner".into(), "guest".into(), false)
+            .await
+            .unwrap();
+        join(&state, &room, "guest").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn disconnect_after_kick_is_not_announced() {
+        let state = AppState::for_tests();
+        let room = open_room(&state, "owner").await;
+        join(&state, &room, "guest").await.unwrap();
+        kick_player(&room, "owner".into(), "guest".into(), true)
+            .await
+            .unwrap();
+
+        // 내보내진 뒤 연결이 끊겨도 남은 구성원에게 disconnected를 보내지 않음
+        let mut events = room.tx.subscribe();
+        let (tx, _rx) = connection();
+        leave_room(&room, "guest".into(), Some(tx)).await;
+        assert!(matches!(
+            events.try_recv(),
+            Err(broadcast::error::TryRecvError::Empty)
+        ));
+    }
+
+    /// 차례인 플레이어가 던지고, 둘 수 있으면 첫 번째 합법 수를 둡니다.
+    pub(crate) async fn play_turn(room: &Arc<Room>) {
+        let snap = snapshot_room(room).await.unwrap();
+        let side = snap.game.turn;
+        let player_id = snap.seats[&side].clone();
+        roll_sticks(room, player_id.clone()).await.unwrap();
+
+        let snap = snapshot_room(room).await.unwrap();
+        if snap.game.turn != side || snap.game.game_over {
+            return; // 둘 수가 없어 자동으로 넘어감
+        }
+        let roll = snap.game.last_roll.unwrap();
+        let (piece_index, from, to) = snap.game.legal_moves(side, roll)[0];
+        let mv = MoveRequest {
+            side,
+            piece_index,
+            from,
+            to,
+            roll,
+        };
+        move_piece(room, player_id, mv).await.unwrap();
+    }
+
+    /// 게임이 끝날 때까지 두고, 그동안 방에 나간 MATCH_ENDED의 시리즈를 모아 돌려줍니다.
+    async fn play_out(
+        room: &Arc<Room>,
+        events: &mut broadcast::Receiver<Arc<Outgoing>>,
+    ) -> Vec<(String, MatchSeries)> {
+        let mut match_ended = Vec::new();
+        for _ in 0..5_000 {
+            if snapshot_room(room).await.unwrap().status == RoomStatus::Finished {
+                return match_ended;
+            }
+            play_turn(room).await;
+            // 한 차례의 이벤트는 채널 용량보다 훨씬 적으므로 놓치지 않음
+            while let Ok(out) = events.try_recv() {
+                if let ServerMsg::MatchEnded { winner, series, .. } = out.msg() {
+                    match_ended.push((winner.clone(), series.clone()));
+                }
+            }
+        }
+        panic!("게임이 끝나지 않음");
+    }
+
+    async fn ready_both(room: &Arc<Room>) {
+        set_ready(room, "alice".into(), true).await;
+        set_ready(room, "bob".into(), true).await;
+    }
+
+    #[tokio::test]
+    async fn series_follows_players_across_side_swaps() {
+        let state = AppState::for_tests();
+        let room = open_room(&state, "alice").await;
+        join(&state, &room, "bob").await.unwrap();
+        let mut events = room.tx.subscribe();
+        let update = RoomSettingsUpdate {
+            best_of: Some(3),
+            ..Default::default()
+        };
+        update_room_settings(&room, "alice".into(), update)
+            .await
+            .unwrap();
+        ready_both(&room).await;
+        start_game(&room, "alice".into()).await.unwrap();
+
+        // 게임 중에는 자리를 바꿀 수 없음
+        let err = swap_sides(&room, "alice".into(), true).await;
+        assert!(matches!(err, Err(ServerError::GameInProgress)));
+        let err = choose_seat(&room, "bob".into(), 'W').await;
+        assert!(matches!(err, Err(ServerError::GameInProgress)));
+
+        let mut expected: BTreeMap<String, u32> =
+            [("alice".to_string(), 0), ("bob".to_string(), 0)].into();
+        for game_number in 1..=3 {
+            let match_ended = play_out(&room, &mut events).await;
+            let snap = snapshot_room(&room).await.unwrap();
+            let record = snap.last_game.unwrap();
+            // 승리는 이긴 진영에 앉아 있던 플레이어 ID로 기록
+            assert_eq!(record.winner_id, snap.seats[&record.winner]);
+            *expected.get_mut(&record.winner_id).unwrap() += 1;
+            assert_eq!(snap.series.game_number, game_number);
+            assert_eq!(snap.series.wins, expected);
+
+            // 과반(2승)에 닿은 판에서 딱 한 번만 MATCH_ENDED
+            if expected[&record.winner_id] < 2 {
+                assert!(match_ended.is_empty());
+            } else {
+                assert_eq!(match_ended.len(), 1);
+                let (winner, series) = &match_ended[0];
+                assert_eq!(winner, &record.winner_id);
+                assert_eq!(series.wins, expected);
+                return;
+            }
+
+            let seats = snap.seats.clone();
+            if game_number == 1 {
+                // 상대 좌석은 혼자 차지할 수 없고, 합의로 진영을 바꾼 뒤 다시 시작
+                let alice_side = if seats[&'W'] == "alice" { 'W' } else { 'B' };
+                let bob_side = if alice_side == 'W' { 'B' } else { 'W' };
+                let err = choose_seat(&room, "alice".into(), bob_side).await;
+                assert!(matches!(err, Err(ServerError::SeatTaken { side }) if side == bob_side));
+                swap_sides(&room, "alice".into(), true).await.unwrap();
+                swap_sides(&room, "bob".into(), true).await.unwrap();
+                ready_both(&room).await;
+                start_game(&room, "alice".into()).await.unwrap();
+            } else {
+                // 재대결은 지난 판과 진영을 바꿔 시작
+                offer_rematch(&room, "bob".into()).await.unwrap();
+                accept_rematch(&room, "alice".into()).await.unwrap();
+            }
+            let snap = snapshot_room(&room).await.unwrap();
+            assert_eq!(snap.seats[&'W'], seats[&'B']);
+            assert_eq!(snap.seats[&'B'], seats[&'W']);
+            assert_eq!(snap.series.wins, expected);
+        }
+        panic!("세 판 안에 승부가 나야 함");
+    }
+
+    #[tokio::test]
+    async fn finished_games_rank_rooms_by_rating() {
+        let state = AppState::for_tests();
+        let room = open_room(&state, "alice").await;
+        join(&state, &room, "bob").await.unwrap();
+        let mut events = room.tx.subscribe();
+        ready_both(&room).await;
+        start_game(&room, "alice".into()).await.unwrap();
+        play_out(&room, &mut events).await;
+
+        let record = snapshot_room(&room).await.unwrap().last_game.unwrap();
+        let winner = record.winner_id.clone();
+        let loser = if winner == "alice" { "bob" } else { "alice" };
+        assert_eq!(state.ratings.get(&winner), 1216);
+        assert_eq!(state.ratings.get(loser), 1184);
+
+        // 새 방마다 방장이 혼자 앉아 있으므로 방장 레이팅 순
+        let mut by_owner = HashMap::new();
+        for owner in [loser, "carol", winner.as_str()] {
+            by_owner.insert(open_room(&state, owner).await.id.clone(), owner);
+        }
+        let query = RoomQuery::from_json(&json!({"sort": "rating"})).unwrap();
+        let page = state.lobby.page(&query);
+        assert_eq!(page.rooms[0].rating, 1216);
+        // 끝난 방은 두 사람 평균이라 그대로 1200
+        let owners: Vec<&str> = page
+            .rooms
+            .iter()
+            .filter_map(|r| by_owner.get(&r.id).copied())
+            .collect();
+        assert_eq!(owners, [winner.as_str(), "carol", loser]);
+    }
+
+    #[tokio::test]
+    async fn seats_move_only_to_free_sides_and_swap_needs_consent() {
+        let state = AppState::for_tests();
+        let room = open_room(&state, "alice").await;
+        let seats = || async { snapshot_room(&room).await.unwrap().seats };
+
+        // 혼자일 때는 빈 좌석으로 옮길 수 있지만 교체는 불가
+        choose_seat(&room, "alice".into(), 'B').await.unwrap();
+        let err = swap_sides(&room, "alice".into(), true).await;
+        assert!(matches!(err, Err(ServerError::NeedTwoPlayers)));
+
+        // 다음 입장자는 남은 좌석에 앉음
+        join(&state, &room, "bob").await.unwrap();
+        assert_eq!(seats().await[&'W'], "bob");
+        let err = choose_seat(&room, "alice".into(), 'W').await;
+        assert!(matches!(err, Err(ServerError::SeatTaken { side: 'W' })));
+
+        // 요청한 쪽이 다시 동의해도 바뀌지 않고, 거절하면 요청이 사라짐
+        swap_sides(&room, "alice".into(), true).await.unwrap();
+        swap_sides(&room, "alice".into(), true).await.unwrap();
+        assert_eq!(seats().await[&'B'], "alice");
+        swap_sides(&room, "bob".into(), false).await.unwrap();
+        swap_sides(&room, "bob".into(), true).await.unwrap();
+        assert_eq!(seats().await[&'B'], "alice");
+
+        // 상대가 요청한 뒤 동의하면 교체
+        swap_sides(&room, "alice".into(), true).await.unwrap();
+        assert_eq!(seats().await[&'W'], "alice");
+        assert_eq!(seats().await[&'B'], "bob");
+    }
+
+    fn turn_changed(new_turn: &str) -> Arc<Outgoing> {
+        Outgoing::new(ServerMsg::TurnChanged {
+            room_id: "r".into(),
+            game_id: "g".into(),
+            new_turn: new_turn.into(),
+            reason: "normal_move".into(),
+        })
+    }
+
+    fn system_status(status: &str) -> Arc<Outgoing> {
+        Outgoing::new(ServerMsg::PlayerStatus {
+            room_id: "r".into(),
+            player_id: "system".into(),
+            status: status.into(),
+            last_seen: 0,
+            message: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn spectator_relay_delays_game_end_and_flushes_on_close() {
+        let (cmd_tx, _cmd_rx) = mpsc::channel(1);
+        let room = Arc::new(Room {
+            id: "r".into(),
+            tx: broadcast::channel(16).0,
+            spectator_tx: broadcast::channel(16).0,
+            cmd_tx,
+        });
+        let (_delay_tx, delay_rx) = watch::channel(SpectatorDelay::Seconds(3600));
+        spawn_spectator_relay(&room, delay_rx);
+        let mut spectator = room.spectator_tx.subscribe();
+
+        room.tx.send(turn_changed("B")).unwrap();
+        room.tx.send(system_status("game_finished")).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // 게임 종료 알림도 지연된 수보다 먼저 나가지 않음
+        assert!(spectator.try_recv().is_err());
+
+        // 방이 닫히면 남은 이벤트를 순서대로 보냄
+        drop(room);
+        let first = spectator.recv().await.unwrap();
+        assert!(matches!(first.msg(), ServerMsg::TurnChanged { .. }));
+        let second = spectator.recv().await.unwrap();
+        assert!(matches!(
+            second.msg(),
+            ServerMsg::PlayerStatus { status, .. } if status == "game_finished"
+        ));
+    }
+
+    #[tokio::test]
+    async fn spectator_relay_resyncs_game_state_after_lagging() {
+        let (cmd_tx, mut cmd_rx) = mpsc::channel(1);
+        let room = Arc::new(Room {
+            id: "r".into(),
+            tx: broadcast::channel(4).0,
+            spectator_tx: broadcast::channel(64).0,
+            cmd_tx,
+        });
+        let (_delay_tx, delay_rx) = watch::channel(SpectatorDelay::Moves(1));
+        spawn_spectator_relay(&room, delay_rx);
+        let mut spectator = room.spectator_tx.subscribe();
+
+        // 중계 태스크가 돌기 전에 채널 용량보다 많이 보내서 밀리게 함
+        for _ in 0..10 {
+            room.tx.send(turn_changed("W")).unwrap();
+        }
+        let Some(RoomCmd::GameStateSync { reply }) = cmd_rx.recv().await else {
+            panic!("밀린 뒤 게임 상태를 요청하지 않음");
+        };
+        reply
+            .send(ServerMsg::GameStateSync {
+                room_id: "r".into(),
+                game_id: "g".into(),
+                status: RoomStatus::Playing,
+                game_state: Some(json!({"turn": "W"})),
+            })
+            .unwrap();
+
+        // 다시 보내는 상태도 관전 지연(1수)을 거침
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(spectator.try_recv().is_err());
+        room.tx
+            .send(Outgoing::new(ServerMsg::PieceMoved {
+                room_id: "r".into(),
+                game_id: "g".into(),
+                move_: json!({}),
+                game_state: json!({}),
+            }))
+            .unwrap();
+        let first = spectator.recv().await.unwrap();
+        assert!(matches!(
+            first.msg(),
+            ServerMsg::GameStateSync { game_state: Some(state), .. } if state["turn"] == "W"
+        ));
     }
 }
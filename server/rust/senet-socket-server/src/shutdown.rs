@@ -0,0 +1,392 @@
+use serde::Serialize;
+use std::{path::PathBuf, time::Duration};
+use tokio::sync::{watch, Mutex};
+use tracing::{error, info};
+
+use crate::{
+    messages::ServerMsg,
+    snapshot,
+    types::{ts, AppState, RoomStatus},
+};
+
+// ========================= 종료 / 드레인 =========================
+
+/// 드레인을 시작한 이유. 클라이언트 알림 문구와 관리자 API 응답에 그대로 쓰입니다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DrainReason {
+    /// SIGTERM/Ctrl+C - 드레인이 끝나면 프로세스가 종료됨
+    Shutdown,
+    /// 관리자 API - 드레인이 끝나도 프로세스는 남음
+    Maintenance,
+}
+
+impl DrainReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DrainReason::Shutdown => "shutdown",
+            DrainReason::Maintenance => "maintenance",
+        }
+    }
+}
+
+/// 서버의 드레인 단계. 연결마다 구독해서 알림을 보내고 소켓을 닫습니다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "phase", rename_all = "lowercase")]
+pub enum DrainPhase {
+    Running,
+    /// 새 방/새 게임을 받지 않고 진행 중인 게임이 끝나기를 기다림
+    #[serde(rename_all = "camelCase")]
+    Draining {
+        reason: DrainReason,
+        deadline: u64,
+        seconds_left: u64,
+    },
+    /// 남은 방을 스냅샷에 저장했고 모든 연결을 닫음
+    #[serde(rename_all = "camelCase")]
+    Closed {
+        reason: DrainReason,
+    },
+}
+
+impl DrainPhase {
+    /// 이 단계로 바뀔 때 연결에 보낼 알림. `was_draining`은 그 연결이 이미 종료 예고를 받았는지 여부.
+    pub fn notice(&self, was_draining: bool) -> Option<ServerMsg> {
+        match *self {
+            DrainPhase::Draining {
+                reason,
+                deadline,
+                seconds_left,
+            } => Some(ServerMsg::ServerShuttingDown {
+                reason: reason.as_str().to_string(),
+                deadline,
+                seconds_left,
+                message: None,
+            }),
+            DrainPhase::Running if was_draining => {
+                Some(ServerMsg::ShutdownCancelled { message: None })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 드레인 상태와 설정. `AppState`에 하나 두고 모든 연결과 방 태스크가 함께 봅니다.
+pub struct Drain {
+    phase: watch::Sender<DrainPhase>,
+    /// 진행 중인 게임을 기다리는 최대 시간
+    grace: Duration,
+    snapshot_path: PathBuf,
+    // 종료 신호와 관리자 드레인이 겹쳐도 스냅샷 저장 → 연결 종료를 한 번만 하도록
+    finish: Mutex<()>,
+}
+
+impl Drain {
+    pub fn new(grace: Duration, snapshot_path: PathBuf) -> Self {
+        Self {
+            phase: watch::channel(DrainPhase::Running).0,
+            grace,
+            snapshot_path,
+            finish: Mutex::new(()),
+        }
+    }
+
+    pub fn grace(&self) -> Duration {
+        self.grace
+    }
+
+    pub fn phase(&self) -> DrainPhase {
+        *self.phase.borrow()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<DrainPhase> {
+        self.phase.subscribe()
+    }
+
+    /// 새 방과 새 게임을 받을 수 있는지 여부
+    pub fn is_accepting(&self) -> bool {
+        self.phase() == DrainPhase::Running
+    }
+
+    /// 점검 드레인(또는 그 뒤 닫힌 상태)을 풀고 평소처럼 운영합니다. 바뀌었으면 `true`.
+    /// 종료 신호로 시작한 드레인은 취소할 수 없습니다.
+    pub fn cancel(&self) -> bool {
+        self.phase.send_if_modified(|phase| match phase {
+            DrainPhase::Draining {
+                reason: DrainReason::Maintenance,
+                ..
+            }
+            | DrainPhase::Closed {
+                reason: DrainReason::Maintenance,
+            } => {
+                *phase = DrainPhase::Running;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// 드레인을 시작합니다. 이미 드레인 중이면 종료 신호일 때만 이유를 바꾸고 마감은 더 이른 쪽으로.
+    /// 단계가 바뀌었으면 `true`. 진행은 `drain`이 맡습니다.
+    pub fn begin(&self, reason: DrainReason, grace: Duration) -> bool {
+        let deadline = ts().saturating_add(grace.as_millis() as u64);
+        let started = self.phase.send_if_modified(|phase| match phase {
+            DrainPhase::Running => {
+                *phase = DrainPhase::Draining {
+                    reason,
+                    deadline,
+                    seconds_left: grace.as_secs(),
+                };
+                true
+            }
+            DrainPhase::Draining {
+                reason: current,
+                deadline: current_deadline,
+                seconds_left,
+            } if reason == DrainReason::Shutdown && *current != reason => {
+                *current = reason;
+                if deadline < *current_deadline {
+                    *current_deadline = deadline;
+                    *seconds_left = grace.as_secs();
+                }
+                true
+            }
+            _ => false,
+        });
+        if started {
+            info!(
+                "🚧 드레인 시작 ({}): 새 방을 받지 않음, 최대 {}초 동안 진행 중인 게임을 기다림",
+                reason.as_str(),
+                grace.as_secs()
+            );
+        }
+        started
+    }
+
+    fn tick(&self, left: u64) {
+        self.phase.send_modify(|phase| {
+            if let DrainPhase::Draining { seconds_left, .. } = phase {
+                *seconds_left = left;
+            }
+        });
+    }
+
+    fn deadline(&self) -> Option<u64> {
+        match self.phase() {
+            DrainPhase::Draining { deadline, .. } => Some(deadline),
+            _ => None,
+        }
+    }
+}
+
+/// 카운트다운 알림을 보낼 남은 초 (매초 보내면 너무 많음)
+fn announce_at(seconds_left: u64) -> bool {
+    matches!(seconds_left % 60, 0) || matches!(seconds_left, 30 | 10 | 5 | 4 | 3 | 2 | 1)
+}
+
+/// 드레인을 끝까지 진행합니다: 새 방을 막고 카운트다운을 알린 뒤, 진행 중인 게임이 모두 끝나거나
+/// 유예 시간이 지나면 남은 방을 스냅샷에 저장하고 모든 연결을 닫습니다.
+/// 닫힌 상태로 끝나면 `true`, 도중에 관리자가 취소하면 `false`.
+pub async fn drain(state: &AppState, reason: DrainReason, grace: Duration) -> bool {
+    let drain = &state.drain;
+    drain.begin(reason, grace);
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    ticker.tick().await; // 첫 틱은 바로 끝남 - 게임이 없어도 최소 1초는 예고
+    loop {
+        ticker.tick().await;
+        let Some(deadline) = drain.deadline() else {
+            break;
+        };
+        let playing = state.lobby.count_status(RoomStatus::Playing);
+        let left = deadline.saturating_sub(ts()).div_ceil(1000);
+        if playing == 0 {
+            info!("🏁 진행 중인 게임이 없어 드레인을 마칩니다");
+            break;
+        }
+        if left == 0 {
+            info!(
+                "⏰ 드레인 유예 시간 종료 - 진행 중인 게임 {}개를 저장합니다",
+                playing
+            );
+            break;
+        }
+        if announce_at(left) {
+            drain.tick(left);
+        }
+    }
+    finish(state).await
+}
+
+/// 스냅샷을 저장한 뒤 닫힌 단계로 바꿉니다. 그 사이 취소되었으면 `false`.
+async fn finish(state: &AppState) -> bool {
+    let drain = &state.drain;
+    let _guard = drain.finish.lock().await;
+    let reason = match drain.phase() {
+        DrainPhase::Draining { reason, .. } => reason,
+        DrainPhase::Closed { .. } => return true,
+        DrainPhase::Running => {
+            info!("↩️ 드레인이 취소되었습니다");
+            return false;
+        }
+    };
+
+    // 소켓을 닫으면 플레이어가 방을 나가므로 그 전에 저장
+    match snapshot::save(state, &drain.snapshot_path).await {
+        Ok(count) => info!(
+            "💾 방 {}개 스냅샷 저장: {}",
+            count,
+            drain.snapshot_path.display()
+        ),
+        Err(e) => error!("❌ 방 스냅샷 저장 실패: {}", e),
+    }
+    drain.phase.send_replace(DrainPhase::Closed { reason });
+    info!("🔌 모든 연결을 닫습니다");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn drain() -> Drain {
+        Drain::new(
+            Duration::from_secs(60),
+            std::env::temp_dir().join("unused.json"),
+        )
+    }
+
+    fn deadline(drain: &Drain) -> u64 {
+        drain.deadline().expect("드레인 중이어야 함")
+    }
+
+    #[test]
+    fn maintenance_drain_can_be_cancelled() {
+        let drain = drain();
+        assert!(drain.is_accepting());
+        assert!(!drain.cancel());
+
+        assert!(drain.begin(DrainReason::Maintenance, Duration::from_secs(30)));
+        assert!(!drain.is_accepting());
+        assert!(matches!(
+            drain.phase(),
+            DrainPhase::Draining {
+                reason: DrainReason::Maintenance,
+                seconds_left: 30,
+                ..
+            }
+        ));
+        // 같은 이유로 다시 시작해도 바뀌지 않음
+        assert!(!drain.begin(DrainReason::Maintenance, Duration::from_secs(5)));
+
+        assert!(drain.cancel());
+        assert_eq!(drain.phase(), DrainPhase::Running);
+        assert!(drain.is_accepting());
+        assert!(!drain.cancel());
+    }
+
+    #[test]
+    fn huge_grace_saturates_the_deadline() {
+        let drain = drain();
+        assert!(drain.begin(DrainReason::Maintenance, Duration::MAX));
+        assert_eq!(deadline(&drain), u64::MAX);
+    }
+
+    #[test]
+    fn shutdown_overrides_maintenance_and_keeps_earlier_deadline() {
+        let drain = drain();
+        drain.begin(DrainReason::Maintenance, Duration::from_secs(30));
+        let maintenance_deadline = deadline(&drain);
+
+        // 종료 유예가 더 길면 점검 마감을 그대로 둠
+        assert!(drain.begin(DrainReason::Shutdown, Duration::from_secs(600)));
+        assert_eq!(deadline(&drain), maintenance_deadline);
+        assert!(matches!(
+            drain.phase(),
+            DrainPhase::Draining {
+                reason: DrainReason::Shutdown,
+                seconds_left: 30,
+                ..
+            }
+        ));
+
+        // 종료 드레인은 취소할 수 없고 다시 시작해도 그대로
+        assert!(!drain.cancel());
+        assert!(!drain.begin(DrainReason::Shutdown, Duration::from_secs(1)));
+        assert!(!drain.begin(DrainReason::Maintenance, Duration::from_secs(1)));
+        assert_eq!(deadline(&drain), maintenance_deadline);
+    }
+
+    #[test]
+    fn shutdown_takes_earlier_deadline() {
+        let drain = drain();
+        drain.begin(DrainReason::Maintenance, Duration::from_secs(600));
+        let maintenance_deadline = deadline(&drain);
+        assert!(drain.begin(DrainReason::Shutdown, Duration::from_secs(10)));
+        assert!(deadline(&drain) < maintenance_deadline);
+        assert!(matches!(
+            drain.phase(),
+            DrainPhase::Draining {
+                reason: DrainReason::Shutdown,
+                seconds_left: 10,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn countdown_announcements() {
+        let announced: Vec<u64> = (1..=130).rev().filter(|&s| announce_at(s)).collect();
+        assert_eq!(announced, [120, 60, 30, 10, 5, 4, 3, 2, 1]);
+        assert!(announce_at(0));
+    }
+
+    #[test]
+    fn notices_follow_phase() {
+        let draining = DrainPhase::Draining {
+            reason: DrainReason::Shutdown,
+            deadline: 1,
+            seconds_left: 10,
+        };
+        assert!(matches!(
+            draining.notice(false),
+            Some(ServerMsg::ServerShuttingDown {
+                seconds_left: 10,
+                ..
+            })
+        ));
+        assert!(matches!(
+            DrainPhase::Running.notice(true),
+            Some(ServerMsg::ShutdownCancelled { .. })
+        ));
+        assert!(DrainPhase::Running.notice(false).is_none());
+        let closed = DrainPhase::Closed {
+            reason: DrainReason::Shutdown,
+        };
+        assert!(closed.notice(true).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_without_games_closes_and_saves_snapshot() {
+        let path = std::env::temp_dir().join(format!("senet-drain-{}.json", uuid::Uuid::new_v4()));
+        let state = AppState {
+            drain: Arc::new(Drain::new(Duration::from_secs(60), path.clone())),
+            ..AppState::for_tests()
+        };
+
+        assert!(super::drain(&state, DrainReason::Maintenance, Duration::from_secs(60)).await);
+        assert_eq!(
+            state.drain.phase(),
+            DrainPhase::Closed {
+                reason: DrainReason::Maintenance
+            }
+        );
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+
+        // 점검으로 닫힌 서버는 다시 열 수 있음
+        assert!(state.drain.cancel());
+        assert!(state.drain.is_accepting());
+    }
+}
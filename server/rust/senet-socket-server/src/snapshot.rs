@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    game::GameState,
+    room::{restore_room, snapshot_room},
+    shutdown::DrainPhase,
+    stats::{GameRecord, GameStats, MoveRecord},
+    types::{
+        ts, AppState, ChatPolicy, ClockSettings, MatchSeries, Room, RoomStatus, RuleVariant,
+        SpectatorPolicy, StartPolicy, Visibility,
+    },
+};
+
+// ========================= 방 스냅샷 =========================
+
+/// 스냅샷 파일 형식 버전. 읽을 수 없는 버전이면 복원하지 않습니다.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// 재시작 후 복원할 방 하나. 연결(전송 채널)과 관전자는 남기지 않습니다.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomSnapshot {
+    pub id: String,
+    pub name: String,
+    pub status: RoomStatus,
+    pub owner: String,
+    /// Argon2id PHC 문자열 (평문 비밀번호는 어디에도 남지 않음)
+    pub password_hash: Option<String>,
+    pub max_players: usize,
+    pub players: Vec<PlayerSnapshot>,
+    pub spectator_policy: SpectatorPolicy,
+    pub chat_policy: ChatPolicy,
+    pub variant: RuleVariant,
+    pub clock: ClockSettings,
+    pub start_policy: StartPolicy,
+    pub series: MatchSeries,
+    pub rematch_offer: Option<String>,
+    pub visibility: Visibility,
+    pub invite_code: String,
+    pub seats: BTreeMap<char, String>,
+    pub swap_request: Option<String>,
+    pub banned: HashSet<String>,
+    pub ready: HashMap<String, bool>,
+    pub game: GameState,
+    pub game_id: String,
+    pub game_started_at: u64,
+    pub stats: GameStats,
+    pub moves: Vec<MoveRecord>,
+    pub last_game: Option<GameRecord>,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSnapshot {
+    pub id: String,
+    pub name: String,
+    pub joined_at: u64,
+    pub session_token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    pub version: u32,
+    pub saved_at: u64,
+    pub rooms: Vec<RoomSnapshot>,
+}
+
+/// 스냅샷 저장 위치와 주기, 재접속 유예 시간
+#[derive(Clone, Debug)]
+pub struct SnapshotConfig {
+    pub path: PathBuf,
+    pub interval: Duration,
+    pub resume_grace: Duration,
+}
+
+/// 모든 방 태스크에서 현재 상태를 받아 모읍니다.
+pub async fn capture(state: &AppState) -> SnapshotFile {
+    let rooms: Vec<Arc<Room>> = state.rooms.iter().map(|r| r.value().clone()).collect();
+    let mut snapshots = Vec::with_capacity(rooms.len());
+    for room in rooms {
+        if let Some(snap) = snapshot_room(&room).await {
+            snapshots.push(snap);
+        }
+    }
+    SnapshotFile {
+        version: SNAPSHOT_VERSION,
+        saved_at: ts(),
+        rooms: snapshots,
+    }
+}
+
+/// 스냅샷을 임시 파일에 쓴 뒤 이름을 바꿔서, 쓰는 도중에 죽어도 이전 스냅샷이 남게 합니다.
+pub async fn save(state: &AppState, path: &Path) -> io::Result<usize> {
+    let file = capture(state).await;
+    let count = file.rooms.len();
+    let bytes = serde_json::to_vec(&file)?;
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &path)
+    })
+    .await
+    .map_err(io::Error::other)??;
+    Ok(count)
+}
+
+/// 스냅샷 파일을 읽습니다. 파일이 없으면 `None`.
+pub fn load(path: &Path) -> io::Result<Option<SnapshotFile>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    Ok(Some(serde_json::from_slice(&bytes)?))
+}
+
+/// 시작할 때 스냅샷의 방을 모두 되살립니다. 복원한 방 수를 돌려줍니다.
+pub fn restore(state: &AppState, config: &SnapshotConfig) -> usize {
+    let file = match load(&config.path) {
+        Ok(Some(file)) => file,
+        Ok(None) => return 0,
+        Err(e) => {
+            error!(
+                "❌ 방 스냅샷을 읽을 수 없습니다: {} - {}",
+                config.path.display(),
+                e
+            );
+            return 0;
+        }
+    };
+    if file.version != SNAPSHOT_VERSION {
+        warn!(
+            "⚠️ 스냅샷 버전이 달라 복원하지 않습니다: {} (지원: {})",
+            file.version, SNAPSHOT_VERSION
+        );
+        return 0;
+    }
+    let count = file.rooms.len();
+    for snap in file.rooms {
+        restore_room(state, snap, config.resume_grace);
+    }
+    info!(
+        "♻️ 방 {}개 복원 (저장 시각 {}, 재접속 유예 {}초)",
+        count,
+        file.saved_at,
+        config.resume_grace.as_secs()
+    );
+    count
+}
+
+/// 일정 주기로 스냅샷을 저장하는 태스크 (비정상 종료에 대비)
+pub fn spawn_periodic(state: AppState, config: SnapshotConfig) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.tick().await; // 첫 틱은 바로 끝남
+        loop {
+            ticker.tick().await;
+            // 드레인으로 연결을 닫은 뒤에는 방이 비어 가므로 마지막 스냅샷을 덮어쓰지 않음
+            if matches!(state.drain.phase(), DrainPhase::Closed { .. }) {
+                continue;
+            }
+            if let Err(e) = save(&state, &config.path).await {
+                error!("❌ 방 스냅샷 저장 실패: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::ServerError,
+        password::Secret,
+        protocol::Outgoing,
+        room::{
+            create_room, join_room, resume_session, roll_sticks, set_ready, start_game,
+            tests::play_turn,
+        },
+        types::RoomRef,
+    };
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    fn connection() -> mpsc::Sender<Arc<Outgoing>> {
+        mpsc::channel(256).0
+    }
+
+    fn secret(s: &str) -> Secret {
+        serde_json::from_value(json!(s)).unwrap()
+    }
+
+    /// alice(W, 방장)와 bob(B)이 게임을 시작한 방
+    async fn playing_room(state: &AppState) -> Arc<Room> {
+        let (room, _) = create_room(
+            state,
+            connection(),
+            "복원 테스트".into(),
+            None,
+            2,
+            SpectatorPolicy::default(),
+            RuleVariant::Standard,
+            Visibility::Public,
+            "Alice".into(),
+            "alice".into(),
+        )
+        .await
+        .unwrap();
+        join_room(
+            state,
+            connection(),
+            RoomRef::Id(room.id.clone()),
+            None,
+            "Bob".into(),
+            "bob".into(),
+        )
+        .await
+        .unwrap();
+        set_ready(&room, "bob".into(), true).await;
+        start_game(&room, "alice".into()).await.unwrap();
+        room
+    }
+
+    /// 저장 → JSON → 복원한 방
+    async fn round_trip(state: &AppState, grace: Duration) -> (AppState, Arc<Room>, SnapshotFile) {
+        let file = capture(state).await;
+        let bytes = serde_json::to_vec(&file).unwrap();
+        let mut loaded: SnapshotFile = serde_json::from_slice(&bytes).unwrap();
+        let snap = loaded.rooms.remove(0);
+        let room_id = snap.id.clone();
+
+        let restored = AppState::for_tests();
+        restore_room(&restored, snap, grace);
+        let room = restored.rooms.get(&room_id).unwrap().clone();
+        (restored, room, file)
+    }
+
+    #[tokio::test]
+    async fn round_trip_keeps_room_and_game() {
+        let state = AppState::for_tests();
+        let room = playing_room(&state).await;
+        for _ in 0..6 {
+            play_turn(&room).await;
+        }
+
+        let (_restored_state, restored, file) = round_trip(&state, Duration::from_secs(60)).await;
+        let before = &file.rooms[0];
+        let after = snapshot_room(&restored).await.unwrap();
+
+        assert_eq!(after.status, RoomStatus::Playing);
+        assert_eq!(after.owner, "alice");
+        assert_eq!(after.seats, before.seats);
+        assert_eq!(after.seats[&'W'], "alice");
+        assert_eq!(after.ready, before.ready);
+        assert_eq!(after.ready.get("bob"), Some(&true));
+        assert!(!before.moves.is_empty());
+        // 좌석, 준비 상태, 게임(시드와 던진 횟수 포함), 수순, 통계까지 그대로
+        assert_eq!(
+            serde_json::to_value(before).unwrap(),
+            serde_json::to_value(&after).unwrap()
+        );
+        let dice = serde_json::to_value(&after.game.dice).unwrap();
+        assert!(dice["throws"].as_u64().unwrap() >= 6);
+
+        // 같은 시드와 던진 횟수에서 다시 시작하므로 다음 굴림도 같음
+        let side = before.game.turn;
+        let player_id = before.seats[&side].clone();
+        roll_sticks(&room, player_id.clone()).await.unwrap();
+        roll_sticks(&restored, player_id).await.unwrap();
+        let original = snapshot_room(&room).await.unwrap();
+        let replayed = snapshot_room(&restored).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&original.game).unwrap(),
+            serde_json::to_value(&replayed.game).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_checks_token_and_grace() {
+        let state = AppState::for_tests();
+        let room = playing_room(&state).await;
+        play_turn(&room).await;
+
+        let (restored_state, restored, file) = round_trip(&state, Duration::from_millis(200)).await;
+        let token = |id: &str| {
+            let player = file.rooms[0].players.iter().find(|p| p.id == id).unwrap();
+            secret(&player.session_token)
+        };
+
+        // 다른 플레이어의 토큰으로는 재접속 불가
+        let err = resume_session(
+            &restored_state,
+            connection(),
+            restored.id.clone(),
+            "alice".into(),
+            token("bob"),
+        )
+        .await;
+        assert!(matches!(err, Err(ServerError::InvalidSession)));
+
+        let (_, name, _) = resume_session(
+            &restored_state,
+            connection(),
+            restored.id.clone(),
+            "alice".into(),
+            token("alice"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(name, "Alice");
+
+        // 유예 시간 안에 돌아오지 않은 bob은 자리에서 빠짐
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let snap = snapshot_room(&restored).await.unwrap();
+        let ids: Vec<_> = snap.players.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["alice"]);
+        assert!(!snap.seats.values().any(|id| id == "bob"));
+    }
+}
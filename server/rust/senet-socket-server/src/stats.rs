@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::{
+    fairness,
+    types::{MatchSeries, RuleVariant},
+};
+
+// ========================= 게임 통계 =========================
+
+/// 한 진영의 게임 통계
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SideStats {
+    /// 차례를 받은 횟수 (추가 턴은 `extraTurns`로 따로 셈)
+    pub turns: u32,
+    /// 굴림 값별 횟수: [1, 2, 3, 4, 5]
+    pub rolls: [u32; 5],
+    /// 이 게임에서 나온 굴림 값 (순서대로)
+    pub roll_history: Vec<u8>,
+    /// 굴림 분포 기준 운 점수 (`fairness::luck_score`)
+    pub luck: f64,
+    pub captures_made: u32,
+    pub captures_suffered: u32,
+    /// 물칸(27)에 빠진 말 수
+    pub drowned: u32,
+    pub extra_turns: u32,
+    /// 움직일 수 있는 말이 없어 자동으로 넘어간 차례
+    pub forced_passes: u32,
+    /// 차례를 받은 뒤 이동/패스하기까지 걸린 평균 시간 (밀리초)
+    pub avg_think_ms: u64,
+    #[serde(skip)]
+    think_total_ms: u64,
+    #[serde(skip)]
+    decisions: u32,
+}
+
+impl SideStats {
+    fn record_think(&mut self, ms: u64) {
+        self.think_total_ms += ms;
+        self.decisions += 1;
+        self.avg_think_ms = self.think_total_ms / self.decisions as u64;
+    }
+}
+
+/// 진행 중인 게임의 양쪽 통계. 방 태스크가 굴림/이동/패스/턴 전환마다 갱신합니다.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameStats {
+    #[serde(rename = "W")]
+    pub w: SideStats,
+    #[serde(rename = "B")]
+    pub b: SideStats,
+    // 지금 차례인 진영이 차례(또는 추가 턴)를 받은 시각
+    #[serde(skip, default = "Instant::now")]
+    turn_started: Instant,
+}
+
+impl GameStats {
+    /// `first` 진영의 첫 차례로 시작합니다.
+    pub fn new(first: char) -> Self {
+        let mut stats = GameStats {
+            w: SideStats::default(),
+            b: SideStats::default(),
+            turn_started: Instant::now(),
+        };
+        stats.begin_turn(first);
+        stats
+    }
+
+    fn side_mut(&mut self, side: char) -> &mut SideStats {
+        if side == 'W' {
+            &mut self.w
+        } else {
+            &mut self.b
+        }
+    }
+
+    fn think_ms(&self) -> u64 {
+        self.turn_started.elapsed().as_millis() as u64
+    }
+
+    pub fn begin_turn(&mut self, side: char) {
+        self.side_mut(side).turns += 1;
+        self.turn_started = Instant::now();
+    }
+
+    pub fn rolled(&mut self, side: char, roll: u8, can_move: bool) {
+        let stats = self.side_mut(side);
+        if (1..=5).contains(&roll) {
+            stats.rolls[roll as usize - 1] += 1;
+            stats.roll_history.push(roll);
+            stats.luck = fairness::luck_score(&stats.rolls);
+        }
+        if !can_move {
+            stats.forced_passes += 1;
+        }
+    }
+
+    pub fn moved(&mut self, side: char, captured: bool, drowned: bool, extra_turn: bool) {
+        let think = self.think_ms();
+        let enemy = if side == 'W' { 'B' } else { 'W' };
+        let stats = self.side_mut(side);
+        stats.record_think(think);
+        if captured {
+            stats.captures_made += 1;
+        }
+        if drowned {
+            stats.drowned += 1;
+        }
+        if extra_turn {
+            stats.extra_turns += 1;
+            self.turn_started = Instant::now();
+        }
+        if captured {
+            self.side_mut(enemy).captures_suffered += 1;
+        }
+    }
+
+    pub fn passed(&mut self, side: char) {
+        let think = self.think_ms();
+        self.side_mut(side).record_think(think);
+    }
+}
+
+/// 게임 기록에 남기는 한 수. 시드와 이 수순이면 게임을 그대로 재현할 수 있습니다.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MoveRecord {
+    #[serde(rename_all = "camelCase")]
+    Move {
+        side: char,
+        piece_index: usize,
+        from: u8,
+        to: u8,
+        roll: u8,
+        captured: bool,
+        extra_turn: bool,
+        at: u64,
+    },
+    /// 플레이어가 직접 넘긴 차례 (움직일 수 없어 자동으로 넘어간 차례는 남기지 않음)
+    Pass { side: char, at: u64 },
+}
+
+/// 게임 기록에 남기는 한 진영의 플레이어
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordPlayer {
+    pub side: char,
+    pub player_id: String,
+    pub player_name: String,
+}
+
+/// 끝난 게임 한 판의 기록
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRecord {
+    pub game_id: String,
+    pub room_id: String,
+    pub variant: RuleVariant,
+    /// 굴림 난수 시드 (`GameState::with_seed`)
+    pub seed: u64,
+    pub players: Vec<RecordPlayer>,
+    pub winner: char,
+    pub winner_id: String,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub duration_ms: u64,
+    pub stats: GameStats,
+    pub series: MatchSeries,
+    pub moves: Vec<MoveRecord>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn think(ms: u64) {
+        tokio::time::advance(Duration::from_millis(ms)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn counts_turns_moves_and_think_time() {
+        let mut stats = GameStats::new('W');
+        assert_eq!((stats.w.turns, stats.b.turns), (1, 0));
+
+        // W: 4로 추가 턴 → 2로 잡기
+        think(100).await;
+        stats.rolled('W', 4, true);
+        stats.moved('W', false, false, true);
+        think(300).await;
+        stats.rolled('W', 2, true);
+        stats.moved('W', true, false, false);
+        assert_eq!(stats.w.turns, 1);
+        assert_eq!(stats.w.extra_turns, 1);
+        assert_eq!(stats.w.captures_made, 1);
+        assert_eq!(stats.b.captures_suffered, 1);
+        assert_eq!(stats.w.avg_think_ms, 200);
+
+        // B: 움직일 수 없어 자동으로 넘어감 (생각 시간은 세지 않음)
+        stats.begin_turn('B');
+        think(700).await;
+        stats.rolled('B', 3, false);
+        assert_eq!(stats.b.turns, 1);
+        assert_eq!(stats.b.forced_passes, 1);
+        assert_eq!(stats.b.avg_think_ms, 0);
+
+        // W: 물칸에 빠짐
+        stats.begin_turn('W');
+        think(50).await;
+        stats.rolled('W', 1, true);
+        stats.moved('W', false, true, false);
+        assert_eq!(stats.w.turns, 2);
+        assert_eq!(stats.w.drowned, 1);
+        assert_eq!(stats.w.avg_think_ms, 150);
+
+        // B: 직접 넘김
+        stats.begin_turn('B');
+        think(1000).await;
+        stats.rolled('B', 5, true);
+        stats.passed('B');
+        assert_eq!(stats.b.turns, 2);
+        assert_eq!(stats.b.avg_think_ms, 1000);
+        assert_eq!(stats.b.forced_passes, 1);
+
+        assert_eq!(stats.w.roll_history, [4, 2, 1]);
+        assert_eq!(stats.w.rolls, [1, 1, 0, 1, 0]);
+        assert_eq!(stats.b.roll_history, [3, 5]);
+        assert_eq!(stats.w.luck, fairness::luck_score(&stats.w.rolls));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignores_invalid_rolls() {
+        let mut stats = GameStats::new('B');
+        stats.rolled('B', 0, true);
+        stats.rolled('B', 6, true);
+        assert_eq!(stats.b.rolls, [0; 5]);
+        assert!(stats.b.roll_history.is_empty());
+        assert_eq!(stats.b.luck, 0.0);
+    }
+}
@@ -0,0 +1,339 @@
+use rusqlite::{params, Connection, Row};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+use tracing::{error, info};
+
+use crate::stats::{GameRecord, RecordPlayer};
+
+// ========================= 게임 기록 저장소 =========================
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("sqlite: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("record encoding: {0}")]
+    Encoding(#[from] serde_json::Error),
+}
+
+/// 끝난 게임 기록 저장소. 블로킹 API이므로 비동기 코드에서는 `spawn_blocking`으로 부릅니다.
+pub trait GameRepository: Send + Sync {
+    fn save(&self, record: &GameRecord) -> Result<(), StoreError>;
+
+    fn get(&self, game_id: &str) -> Result<Option<GameRecord>, StoreError>;
+
+    /// 최근에 끝난 순서로 최대 `limit`개. `player_id`가 있으면 그 플레이어가 둔 게임만.
+    fn recent(&self, player_id: Option<&str>, limit: usize) -> Result<Vec<GameRecord>, StoreError>;
+}
+
+/// 방 태스크를 막지 않도록 기록 저장을 블로킹 스레드에 맡깁니다. 실패는 로그만 남깁니다.
+pub fn save_in_background(repo: Arc<dyn GameRepository>, record: GameRecord) {
+    tokio::task::spawn_blocking(move || match repo.save(&record) {
+        Ok(()) => info!("💾 게임 기록 저장: 게임={}", record.game_id),
+        Err(e) => error!("❌ 게임 기록 저장 실패: 게임={}, {}", record.game_id, e),
+    });
+}
+
+// ===== 메모리 구현 (테스트용) =====
+
+#[derive(Default)]
+pub struct MemoryGameRepository {
+    games: Mutex<Vec<GameRecord>>,
+}
+
+impl MemoryGameRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GameRepository for MemoryGameRepository {
+    fn save(&self, record: &GameRecord) -> Result<(), StoreError> {
+        let mut games = self.games.lock().unwrap();
+        games.retain(|g| g.game_id != record.game_id);
+        games.push(record.clone());
+        Ok(())
+    }
+
+    fn get(&self, game_id: &str) -> Result<Option<GameRecord>, StoreError> {
+        let games = self.games.lock().unwrap();
+        Ok(games.iter().find(|g| g.game_id == game_id).cloned())
+    }
+
+    fn recent(&self, player_id: Option<&str>, limit: usize) -> Result<Vec<GameRecord>, StoreError> {
+        let mut games: Vec<GameRecord> = self
+            .games
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|g| match player_id {
+                Some(id) => g.players.iter().any(|p| p.player_id == id),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        games.sort_by_key(|g| std::cmp::Reverse(g.ended_at));
+        games.truncate(limit);
+        Ok(games)
+    }
+}
+
+// ===== SQLite 구현 =====
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS games (
+    game_id     TEXT PRIMARY KEY,
+    room_id     TEXT NOT NULL,
+    variant     TEXT NOT NULL,
+    seed        INTEGER NOT NULL,
+    winner      TEXT NOT NULL,
+    winner_id   TEXT NOT NULL,
+    started_at  INTEGER NOT NULL,
+    ended_at    INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    stats       TEXT NOT NULL,
+    series      TEXT NOT NULL,
+    moves       TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS games_ended_at ON games (ended_at);
+CREATE TABLE IF NOT EXISTS game_players (
+    game_id     TEXT NOT NULL REFERENCES games (game_id) ON DELETE CASCADE,
+    side        TEXT NOT NULL,
+    player_id   TEXT NOT NULL,
+    player_name TEXT NOT NULL,
+    PRIMARY KEY (game_id, side)
+);
+CREATE INDEX IF NOT EXISTS game_players_player_id ON game_players (player_id);
+";
+
+const SELECT_GAME: &str = "SELECT game_id, room_id, variant, seed, winner, winner_id, started_at, \
+     ended_at, duration_ms, stats, series, moves FROM games";
+
+/// 로컬 파일 하나에 저장하는 SQLite 저장소
+pub struct SqliteGameRepository {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteGameRepository {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, StoreError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// 저장된 게임 수 (시작 로그용)
+    pub fn count(&self) -> Result<u64, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let n: i64 = conn.query_row("SELECT COUNT(*) FROM games", [], |r| r.get(0))?;
+        Ok(n as u64)
+    }
+
+    fn load(conn: &Connection, row: &Row) -> Result<GameRecord, StoreError> {
+        let game_id: String = row.get(0)?;
+        let players = conn
+            .prepare_cached(
+                "SELECT side, player_id, player_name FROM game_players \
+                 WHERE game_id = ?1 ORDER BY side DESC",
+            )?
+            .query_map([&game_id], |r| {
+                Ok(RecordPlayer {
+                    side: side_char(&r.get::<_, String>(0)?),
+                    player_id: r.get(1)?,
+                    player_name: r.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(GameRecord {
+            game_id,
+            room_id: row.get(1)?,
+            variant: serde_json::from_value(row.get::<_, String>(2)?.into())?,
+            seed: row.get::<_, i64>(3)? as u64,
+            players,
+            winner: side_char(&row.get::<_, String>(4)?),
+            winner_id: row.get(5)?,
+            started_at: row.get::<_, i64>(6)? as u64,
+            ended_at: row.get::<_, i64>(7)? as u64,
+            duration_ms: row.get::<_, i64>(8)? as u64,
+            stats: serde_json::from_str(&row.get::<_, String>(9)?)?,
+            series: serde_json::from_str(&row.get::<_, String>(10)?)?,
+            moves: serde_json::from_str(&row.get::<_, String>(11)?)?,
+        })
+    }
+}
+
+fn side_char(s: &str) -> char {
+    s.chars().next().unwrap_or('W')
+}
+
+impl GameRepository for SqliteGameRepository {
+    fn save(&self, record: &GameRecord) -> Result<(), StoreError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO games (game_id, room_id, variant, seed, winner, winner_id, \
+             started_at, ended_at, duration_ms, stats, series, moves) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                record.game_id,
+                record.room_id,
+                record.variant.as_str(),
+                // u64 시드는 비트 그대로 i64에 담음
+                record.seed as i64,
+                record.winner.to_string(),
+                record.winner_id,
+                record.started_at as i64,
+                record.ended_at as i64,
+                record.duration_ms as i64,
+                serde_json::to_string(&record.stats)?,
+                serde_json::to_string(&record.series)?,
+                serde_json::to_string(&record.moves)?,
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM game_players WHERE game_id = ?1",
+            [&record.game_id],
+        )?;
+        for p in &record.players {
+            tx.execute(
+                "INSERT INTO game_players (game_id, side, player_id, player_name) \
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    record.game_id,
+                    p.side.to_string(),
+                    p.player_id,
+                    p.player_name
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get(&self, game_id: &str) -> Result<Option<GameRecord>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(&format!("{SELECT_GAME} WHERE game_id = ?1"))?;
+        let mut rows = stmt.query([game_id])?;
+        rows.next()?.map(|row| Self::load(&conn, row)).transpose()
+    }
+
+    fn recent(&self, player_id: Option<&str>, limit: usize) -> Result<Vec<GameRecord>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(&format!(
+            "{SELECT_GAME} WHERE ?1 IS NULL OR game_id IN \
+             (SELECT game_id FROM game_players WHERE player_id = ?1) \
+             ORDER BY ended_at DESC LIMIT ?2"
+        ))?;
+        let mut rows = stmt.query(params![player_id, limit as i64])?;
+        let mut games = vec![];
+        while let Some(row) = rows.next()? {
+            games.push(Self::load(&conn, row)?);
+        }
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stats::{GameStats, MoveRecord},
+        types::{MatchSeries, RuleVariant},
+    };
+
+    fn record(game_id: &str, ended_at: u64, players: [(&str, char); 2]) -> GameRecord {
+        let mut stats = GameStats::new('W');
+        stats.rolled('W', 4, true);
+        stats.moved('W', true, false, true);
+        GameRecord {
+            game_id: game_id.into(),
+            room_id: "room".into(),
+            variant: RuleVariant::Standard,
+            seed: u64::MAX - 7,
+            players: players
+                .iter()
+                .map(|(id, side)| RecordPlayer {
+                    side: *side,
+                    player_id: id.to_string(),
+                    player_name: format!("{id}-name"),
+                })
+                .collect(),
+            winner: 'W',
+            winner_id: players[0].0.into(),
+            started_at: ended_at - 1_000,
+            ended_at,
+            duration_ms: 1_000,
+            stats,
+            series: MatchSeries::new(1),
+            moves: vec![
+                MoveRecord::Move {
+                    side: 'W',
+                    piece_index: 4,
+                    from: 9,
+                    to: 13,
+                    roll: 4,
+                    captured: true,
+                    extra_turn: true,
+                    at: ended_at - 500,
+                },
+                MoveRecord::Pass {
+                    side: 'W',
+                    at: ended_at - 100,
+                },
+            ],
+        }
+    }
+
+    fn exercise(repo: &dyn GameRepository) {
+        repo.save(&record("g1", 10_000, [("alice", 'W'), ("bob", 'B')]))
+            .unwrap();
+        repo.save(&record("g2", 20_000, [("carol", 'W'), ("alice", 'B')]))
+            .unwrap();
+        repo.save(&record("g3", 30_000, [("bob", 'W'), ("carol", 'B')]))
+            .unwrap();
+
+        let g1 = repo.get("g1").unwrap().expect("g1 저장됨");
+        assert_eq!(g1.seed, u64::MAX - 7);
+        assert_eq!(g1.players.len(), 2);
+        assert_eq!(g1.players[0].player_id, "alice");
+        assert_eq!(g1.players[0].side, 'W');
+        assert_eq!(g1.stats.w.captures_made, 1);
+        assert_eq!(g1.stats.b.captures_suffered, 1);
+        assert_eq!(g1.stats.w.roll_history, vec![4]);
+        assert_eq!(g1.moves.len(), 2);
+        assert!(repo.get("missing").unwrap().is_none());
+
+        let ids = |games: Vec<GameRecord>| games.into_iter().map(|g| g.game_id).collect::<Vec<_>>();
+        assert_eq!(ids(repo.recent(None, 10).unwrap()), ["g3", "g2", "g1"]);
+        assert_eq!(ids(repo.recent(None, 1).unwrap()), ["g3"]);
+        assert_eq!(ids(repo.recent(Some("alice"), 10).unwrap()), ["g2", "g1"]);
+        assert!(repo.recent(Some("nobody"), 10).unwrap().is_empty());
+
+        // 같은 게임을 다시 저장하면 덮어씀
+        repo.save(&record("g1", 40_000, [("alice", 'W'), ("bob", 'B')]))
+            .unwrap();
+        assert_eq!(ids(repo.recent(None, 10).unwrap()), ["g1", "g3", "g2"]);
+    }
+
+    #[test]
+    fn memory_repository() {
+        exercise(&MemoryGameRepository::new());
+    }
+
+    #[test]
+    fn sqlite_repository() {
+        exercise(&SqliteGameRepository::open_in_memory().unwrap());
+    }
+}
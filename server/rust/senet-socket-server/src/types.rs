@@ -1,23 +1,41 @@
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{sync::Arc, time::SystemTime};
-use tokio::sync::{broadcast, mpsc, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::{
+    error::ServerError,
+    fairness::RollAudit,
+    lobby::Lobby,
+    password::RoomPassword,
+    protocol::Outgoing,
+    rating::Ratings,
+    room::RoomCmd,
+    shutdown::Drain,
+    stats::{GameRecord, GameStats, MoveRecord},
+    store::GameRepository,
+};
 
 // ========================= 공통 타입 =========================
 
+/// 전송 봉투 - 메시지의 `type`/`data`에 `timestamp`를 덧붙입니다.
 #[derive(Debug, Clone, Serialize)]
 pub struct Envelope<T> {
-    pub r#type: String,
-    pub timestamp: u128,
-    pub data: T,
+    #[serde(flatten)]
+    pub msg: T,
+    pub timestamp: u64,
 }
 
-pub fn ts() -> u128 {
+pub fn ts() -> u64 {
     SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
-        .as_millis()
+        .as_millis() as u64
 }
 
 // ========================= 서버 상태 =========================
@@ -25,13 +43,44 @@ pub fn ts() -> u128 {
 #[derive(Clone)]
 pub struct AppState {
     pub rooms: Arc<DashMap<String, Arc<Room>>>,
+    pub lobby: Arc<Lobby>,
+    pub rolls: Arc<RollAudit>,
+    pub games: Arc<dyn GameRepository>,
+    /// 플레이어 ID별 레이팅 (끝난 게임 기록에서 계산)
+    pub ratings: Arc<Ratings>,
+    /// 관리자 API 토큰. 없으면 관리자 API를 쓸 수 없습니다.
+    pub admin_token: Option<Arc<str>>,
+    /// 종료/점검 전 드레인 상태
+    pub drain: Arc<Drain>,
 }
 
+#[cfg(test)]
+impl AppState {
+    /// 테스트용 상태 - 게임 기록은 메모리에만 남기고 스냅샷 파일은 쓰지 않음
+    pub(crate) fn for_tests() -> Self {
+        AppState {
+            rooms: Arc::new(DashMap::new()),
+            lobby: Arc::new(Lobby::new()),
+            rolls: Arc::new(RollAudit::new()),
+            games: Arc::new(crate::store::MemoryGameRepository::new()),
+            ratings: Arc::new(Ratings::new()),
+            admin_token: None,
+            drain: Arc::new(Drain::new(
+                std::time::Duration::from_secs(60),
+                std::env::temp_dir().join("senet-test-rooms.json"),
+            )),
+        }
+    }
+}
+
+/// 방 핸들. 방 상태는 방 태스크(`room::RoomActor`)만 소유하고, 바깥에서는 명령 큐로만 접근합니다.
 #[derive(Clone)]
 pub struct Room {
     pub id: String,
-    pub tx: broadcast::Sender<crate::messages::ServerMsg>,
-    pub inner: Arc<RwLock<RoomInner>>,
+    pub tx: broadcast::Sender<Arc<Outgoing>>,
+    // 관전자 전용 채널 - 중계 태스크가 tx를 구독해서 지연 설정에 맞춰 전달
+    pub spectator_tx: broadcast::Sender<Arc<Outgoing>>,
+    pub cmd_tx: mpsc::Sender<RoomCmd>,
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
@@ -42,68 +91,380 @@ pub enum RoomStatus {
     Finished,
 }
 
-#[derive(Clone)]
+impl RoomStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoomStatus::Waiting => "waiting",
+            RoomStatus::Playing => "playing",
+            RoomStatus::Finished => "finished",
+        }
+    }
+}
+
+/// 방 태스크가 소유하는 방 상태. 잠금 없이 한 태스크에서만 읽고 씁니다.
 pub struct RoomInner {
     pub status: RoomStatus,
     pub name: String,
     pub owner: String, // playerId
-    pub password: Option<String>,
-    pub max_players: usize,               // always 2 for Senet
-    pub players: DashMap<String, Player>, // playerId -> Player
-    #[allow(dead_code)]
-    pub spectators: DashMap<String, Spectator>,
+    pub password: Option<Arc<RoomPassword>>,
+    pub max_players: usize,                      // always 2 for Senet
+    pub players: BTreeMap<String, Player>,       // playerId -> Player
+    pub spectators: BTreeMap<String, Spectator>, // spectatorId -> Spectator
+    pub spectator_policy: SpectatorPolicy,
+    pub chat_policy: ChatPolicy,
+    pub variant: RuleVariant,
+    pub clock: ClockSettings,
+    pub start_policy: StartPolicy,
+    pub series: MatchSeries,
+    // 재대결을 제안하고 상대의 수락을 기다리는 플레이어
+    pub rematch_offer: Option<String>,
+    pub visibility: Visibility,
+    // 초대 코드 (방장이 다시 발급하면 예전 코드는 더 이상 쓸 수 없음)
+    pub invite_code: String,
     // 좌석은 W/B (White/Black)
-    pub seats: DashMap<char, String>, // 'W' or 'B' -> playerId
-    pub ready: DashMap<String, bool>,
+    pub seats: BTreeMap<char, String>,
+    // 진영 교체를 요청하고 상대의 동의를 기다리는 플레이어
+    pub swap_request: Option<String>,
+    // 방장이 차단한 플레이어 ID (같은 ID로는 입장/관전 불가, 재시작해도 유지)
+    pub banned: HashSet<String>,
+    pub ready: HashMap<String, bool>,
     pub game: crate::game::GameState,
     pub game_id: String,
-    pub last_activity: u128,
+    // 지금 게임의 시작 시각과 진영별 통계
+    pub game_started_at: u64,
+    pub stats: GameStats,
+    // 지금 게임의 수순 (게임 기록용)
+    pub moves: Vec<MoveRecord>,
+    // 마지막으로 끝난 게임의 기록
+    pub last_game: Option<GameRecord>,
+    pub created_at: u64,
+    // 로비에 보이는 정보(인원, 상태, 설정)가 마지막으로 바뀐 시각
+    pub updated_at: u64,
+    pub last_activity: u64,
+}
+
+/// 로비 목록에 보여줄 방 요약. 방 태스크가 상태를 바꿀 때마다 로비 색인(`lobby::Lobby`)에 새로 올립니다.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomSummary {
+    pub id: String,
+    pub name: String,
+    pub status: RoomStatus,
+    pub owner: String,
+    pub current_players: usize,
+    pub max_players: usize,
+    pub spectator_count: usize,
+    pub allow_spectators: bool,
+    pub max_spectators: usize,
+    pub spectator_delay: Value,
+    pub has_password: bool,
+    pub variant: RuleVariant,
+    pub clock: ClockSettings,
+    pub visibility: Visibility,
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// 앉은 플레이어들의 평균 레이팅 (빈 방은 0)
+    pub rating: u32,
+}
+
+impl RoomSummary {
+    pub fn free_seats(&self) -> usize {
+        self.max_players.saturating_sub(self.current_players)
+    }
+}
+
+/// 방 설정 전체. 설정이 바뀌면 `ROOM_UPDATED`로 방 구성원에게 보냅니다.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomSettings {
+    pub room_name: String,
+    pub has_password: bool,
+    pub visibility: Visibility,
+    pub variant: RuleVariant,
+    pub clock: ClockSettings,
+    pub start_policy: StartPolicy,
+    pub best_of: u32,
+    pub allow_spectators: bool,
+    pub max_spectators: usize,
+    pub spectator_delay: Value,
+    pub players_see_spectator_chat: bool,
+}
+
+/// 시간 제한: 한 사람당 처음 주어지는 시간과 수마다 더해지는 시간(초).
+/// `initialSecs`가 0이면 시간 제한 없음. 아직 게임 진행에는 적용하지 않고 방 설정으로만 보관합니다.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSettings {
+    pub initial_secs: u64,
+    #[serde(default)]
+    pub increment_secs: u64,
+}
+
+/// 게임을 시작할 때 진영과 선공을 정하는 방식
+/// - fixed: 앉은 자리 그대로, W 선공
+/// - randomSides: 시작할 때 진영을 무작위로 정함 (W 선공)
+/// - openingThrow: 진영은 그대로, 번갈아 막대를 던져 먼저 1이 나온 쪽이 선공
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum StartPolicy {
+    #[default]
+    Fixed,
+    RandomSides,
+    OpeningThrow,
+}
+
+/// 여러 판 승부(best-of-N) 진행 상황. 플레이어 ID 기준이라 진영이 바뀌어도 이어집니다.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchSeries {
+    pub best_of: u32,
+    // 지금(또는 마지막으로) 진행한 판 번호, 1부터
+    pub game_number: u32,
+    pub wins: BTreeMap<String, u32>,
+}
+
+impl MatchSeries {
+    pub const MAX_BEST_OF: u32 = 9;
+
+    pub fn new(best_of: u32) -> Self {
+        MatchSeries {
+            best_of,
+            game_number: 0,
+            wins: BTreeMap::new(),
+        }
+    }
+
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    /// 승부가 났으면 이긴 플레이어 ID
+    pub fn winner(&self) -> Option<&String> {
+        self.wins
+            .iter()
+            .find(|(_, wins)| **wins >= self.wins_needed())
+            .map(|(id, _)| id)
+    }
+
+    /// 새 판을 시작합니다. 승부가 났거나 플레이어가 바뀌었으면 새 시리즈로 다시 셉니다.
+    pub fn begin_game<'a>(&mut self, players: impl IntoIterator<Item = &'a String>) {
+        let players: BTreeMap<String, u32> =
+            players.into_iter().map(|id| (id.clone(), 0)).collect();
+        let same_players = players.keys().eq(self.wins.keys());
+        if self.game_number == 0 || self.winner().is_some() || !same_players {
+            self.wins = players;
+            self.game_number = 1;
+        } else {
+            self.game_number += 1;
+        }
+    }
+
+    pub fn record_win(&mut self, player_id: &str) {
+        if let Some(wins) = self.wins.get_mut(player_id) {
+            *wins += 1;
+        }
+    }
+}
+
+/// 방 공개 범위
+/// - public: 로비 목록에 보이고 방 ID나 초대 코드로 입장
+/// - unlisted: 목록에는 안 보이지만 방 ID나 초대 코드로 입장
+/// - private: 목록에 안 보이고 초대 코드로만 입장
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
+}
+
+/// 입장할 방을 가리키는 방법: 방 ID 또는 초대 코드
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoomRef {
+    Id(String),
+    InviteCode(String),
+}
+
+impl RoomRef {
+    /// 초대 코드가 있으면 초대 코드를, 없으면 방 ID를 씁니다.
+    pub fn from_fields(
+        msg_type: &str,
+        room_id: Option<String>,
+        invite_code: Option<String>,
+    ) -> Result<Self, ServerError> {
+        match (room_id, invite_code.filter(|c| !c.trim().is_empty())) {
+            (_, Some(code)) => Ok(RoomRef::InviteCode(code.trim().to_uppercase())),
+            (Some(id), None) => Ok(RoomRef::Id(id)),
+            (None, None) => Err(ServerError::InvalidMessage {
+                msg_type: msg_type.to_string(),
+                field: "roomId".into(),
+                reason: "either `roomId` or `inviteCode` is required".into(),
+            }),
+        }
+    }
+
+    /// 에러 메시지에 붙일 정보
+    pub fn context(&self) -> Value {
+        match self {
+            RoomRef::Id(id) => json!({"roomId": id}),
+            RoomRef::InviteCode(code) => json!({"inviteCode": code}),
+        }
+    }
+}
+
+/// 게임 규칙 종류. 지금은 표준 규칙(안전칸 15·26, 물칸 27)만 있습니다.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleVariant {
+    #[default]
+    Standard,
+}
+
+impl RuleVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleVariant::Standard => "standard",
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Player {
-    #[allow(dead_code)]
     pub id: String,
     pub name: String,
-    #[allow(dead_code)]
-    pub tx: mpsc::Sender<String>, // 문자열(직렬화된 JSON)을 바로 보냄
+    pub tx: mpsc::Sender<Arc<Outgoing>>, // 인코딩은 연결별 전송 태스크가 담당
+    // 방에 들어온 시각 (재연결해도 유지, 방장 승계 순서에 사용)
+    pub joined_at: u64,
+    // 재접속(RESUME_SESSION)할 때 본인 확인용 토큰. 입장할 때 본인에게만 알려줌
+    pub session_token: String,
+    // 서버 재시작으로 복원된 뒤 아직 다시 접속하지 않았으면, 이 시각(ms)까지 돌아와야 자리가 유지됨
+    pub resume_deadline: Option<u64>,
 }
 
 #[derive(Clone)]
 pub struct Spectator {
     #[allow(dead_code)]
     pub id: String,
-    #[allow(dead_code)]
     pub name: String,
-    #[allow(dead_code)]
-    pub tx: mpsc::Sender<String>,
+    pub tx: mpsc::Sender<Arc<Outgoing>>,
+}
+
+/// 방 하나의 좌석 수 (W/B). 방을 만들 때 `maxPlayers`는 이 값이어야 합니다.
+pub const SEATS: usize = 2;
+
+pub const DEFAULT_MAX_SPECTATORS: usize = 20;
+
+/// 관전 허용 여부, 최대 관전자 수, 관전 중계 지연 (방장이 변경 가능)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SpectatorPolicy {
+    pub allow: bool,
+    pub max: usize,
+    pub delay: SpectatorDelay,
+}
+
+impl Default for SpectatorPolicy {
+    fn default() -> Self {
+        Self {
+            allow: true,
+            max: DEFAULT_MAX_SPECTATORS,
+            delay: SpectatorDelay::Off,
+        }
+    }
+}
+
+/// 관전자에게 게임 이벤트를 늦게 보내는 방식 (대회 중계용)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(tag = "mode", content = "value", rename_all = "lowercase")]
+pub enum SpectatorDelay {
+    Off,
+    Seconds(u64),
+    Moves(u64),
+}
+
+impl SpectatorDelay {
+    /// `{"mode": "off" | "seconds" | "moves", "value": n}` 형식을 해석합니다.
+    pub fn from_json(v: &Value) -> Option<Self> {
+        let value = v.get("value").and_then(|x| x.as_u64()).unwrap_or(0);
+        match v.get("mode").and_then(|x| x.as_str())? {
+            "off" => Some(SpectatorDelay::Off),
+            "seconds" if value > 0 => Some(SpectatorDelay::Seconds(value)),
+            "moves" if value > 0 => Some(SpectatorDelay::Moves(value)),
+            "seconds" | "moves" => Some(SpectatorDelay::Off),
+            _ => None,
+        }
+    }
+
+    pub fn to_json(self) -> Value {
+        match self {
+            SpectatorDelay::Off => json!({"mode": "off", "value": 0}),
+            SpectatorDelay::Seconds(n) => json!({"mode": "seconds", "value": n}),
+            SpectatorDelay::Moves(n) => json!({"mode": "moves", "value": n}),
+        }
+    }
+}
+
+/// 채팅 채널: 플레이어끼리, 관전자끼리, 방 전체
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatChannel {
+    Players,
+    Spectators,
+    All,
+}
+
+impl ChatChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatChannel::Players => "players",
+            ChatChannel::Spectators => "spectators",
+            ChatChannel::All => "all",
+        }
+    }
+}
+
+/// 게임 중 관전자 채팅을 플레이어에게 보여줄지 여부 (방장이 변경 가능)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ChatPolicy {
+    pub players_see_spectator_chat: bool,
+}
+
+impl Default for ChatPolicy {
+    fn default() -> Self {
+        Self {
+            players_see_spectator_chat: true,
+        }
+    }
 }
 
 // ========================= 유틸리티 함수 =========================
 
 pub fn collect_players(inner: &RoomInner) -> Vec<Value> {
     let mut v = vec![];
-    for p in inner.players.iter() {
-        let pid = p.key().clone();
-        let name = p.value().name.clone();
+    for (pid, p) in inner.players.iter() {
+        let pid = pid.clone();
+        let name = p.name.clone();
         let is_owner = inner.owner == pid;
-        let is_ready = inner.ready.get(&pid).map(|r| *r.value()).unwrap_or(false);
+        let is_ready = inner.ready.get(&pid).copied().unwrap_or(false);
+        // 재시작 후 아직 돌아오지 않은 플레이어는 false
+        let connected = p.resume_deadline.is_none();
         let mut side: Option<String> = None;
-        for k in ['W', 'B'] {
-            if inner.seats.get(&k).map(|e| e.value().clone()) == Some(pid.clone()) {
+        for (k, seated) in inner.seats.iter() {
+            if *seated == pid {
                 side = Some(k.to_string());
             }
         }
-        v.push(json!({"playerId":pid,"playerName":name,"isOwner":is_owner,"isReady":is_ready,"side":side.unwrap_or("".into())}));
+        v.push(json!({"playerId":pid,"playerName":name,"isOwner":is_owner,"isReady":is_ready,"side":side.unwrap_or("".into()),"connected":connected}));
     }
     v
 }
 
-pub fn get_str(d: &Value, key: &str) -> String {
-    d.get(key)
-        .and_then(|x| x.as_str())
-        .unwrap_or("")
-        .to_string()
+pub fn collect_spectators(inner: &RoomInner) -> Vec<Value> {
+    inner
+        .spectators
+        .iter()
+        .map(|(id, s)| json!({"playerId": id, "playerName": s.name}))
+        .collect()
 }
 
 /// 플레이어 이름 중복을 방지하기 위해 고유한 표시명을 생성합니다.
@@ -114,11 +475,12 @@ pub fn generate_unique_display_name(inner: &RoomInner, requested_name: &str) ->
         return "Player".to_string();
     }
 
-    // 기존 플레이어들의 이름 목록 수집
-    let existing_names: std::collections::HashSet<String> = inner
+    // 기존 플레이어/관전자들의 이름 목록 수집
+    let existing_names: HashSet<String> = inner
         .players
-        .iter()
-        .map(|entry| entry.value().name.clone())
+        .values()
+        .map(|p| p.name.clone())
+        .chain(inner.spectators.values().map(|s| s.name.clone()))
         .collect();
 
     // 요청된 이름이 이미 존재하지 않으면 그대로 사용
@@ -134,10 +496,74 @@ pub fn generate_unique_display_name(inner: &RoomInner, requested_name: &str) ->
             return candidate_name;
         }
         counter += 1;
-        
+
         // 무한 루프 방지 (최대 999까지)
         if counter > 999 {
-            return format!("{}#{}", base_name, uuid::Uuid::new_v4().to_string()[..8].to_string());
+            return format!("{}#{}", base_name, &uuid::Uuid::new_v4().to_string()[..8]);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn series_needs_a_majority() {
+        assert_eq!(MatchSeries::new(1).wins_needed(), 1);
+        assert_eq!(MatchSeries::new(3).wins_needed(), 2);
+        assert_eq!(MatchSeries::new(5).wins_needed(), 3);
+
+        let players = ids(&["alice", "bob"]);
+        let mut series = MatchSeries::new(3);
+        series.begin_game(&players);
+        series.record_win("alice");
+        assert_eq!(series.winner(), None);
+        series.begin_game(&players);
+        series.record_win("bob");
+        assert_eq!(series.winner(), None);
+        series.begin_game(&players);
+        assert_eq!(series.game_number, 3);
+        series.record_win("alice");
+        assert_eq!(series.winner().map(String::as_str), Some("alice"));
+    }
+
+    #[test]
+    fn series_counts_wins_by_player_id() {
+        let mut series = MatchSeries::new(3);
+        // 좌석 순서(W/B)가 바뀌어도 같은 플레이어면 같은 시리즈
+        series.begin_game(&ids(&["alice", "bob"]));
+        series.record_win("alice");
+        series.begin_game(&ids(&["bob", "alice"]));
+        assert_eq!(series.game_number, 2);
+        assert_eq!(series.wins["alice"], 1);
+        assert_eq!(series.wins["bob"], 0);
+
+        // 좌석에 없는 ID의 승리는 세지 않음
+        series.record_win("carol");
+        assert!(!series.wins.contains_key("carol"));
+    }
+
+    #[test]
+    fn series_restarts_after_a_winner_or_new_player() {
+        let mut series = MatchSeries::new(1);
+        series.begin_game(&ids(&["alice", "bob"]));
+        series.record_win("bob");
+        assert_eq!(series.winner().map(String::as_str), Some("bob"));
+        series.begin_game(&ids(&["alice", "bob"]));
+        assert_eq!(series.game_number, 1);
+        assert_eq!(series.winner(), None);
+
+        let mut series = MatchSeries::new(3);
+        series.begin_game(&ids(&["alice", "bob"]));
+        series.record_win("alice");
+        series.begin_game(&ids(&["alice", "carol"]));
+        assert_eq!(series.game_number, 1);
+        assert_eq!(series.wins["alice"], 0);
+        assert_eq!(series.wins["carol"], 0);
+    }
+}